@@ -0,0 +1,25 @@
+#![no_main]
+
+use cozy_uci::command::UciCommand;
+use cozy_uci::UciFormatOptions;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&flags, data)) = data.split_first() else {
+        return;
+    };
+    let Ok(cmd_str) = std::str::from_utf8(data) else {
+        return;
+    };
+    let options = UciFormatOptions {
+        chess960: flags & 1 != 0,
+        wdl: flags & 2 != 0,
+        ..Default::default()
+    };
+    if let Ok(cmd) = UciCommand::parse_from(cmd_str, &options) {
+        let formatted = cmd.format(&options);
+        let cmd_roundtripped = UciCommand::parse_from(&formatted, &options)
+            .expect("failed to parse reformatted command");
+        assert_eq!(cmd, cmd_roundtripped, "roundtripped command is not identical");
+    }
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use cozy_uci::remark::UciRemark;
+use cozy_uci::UciFormatOptions;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&flags, data)) = data.split_first() else {
+        return;
+    };
+    let Ok(rmk_str) = std::str::from_utf8(data) else {
+        return;
+    };
+    let options = UciFormatOptions {
+        chess960: flags & 1 != 0,
+        wdl: flags & 2 != 0,
+        ..Default::default()
+    };
+    if let Ok(rmk) = UciRemark::parse_from(rmk_str, &options) {
+        let formatted = rmk.format(&options);
+        let rmk_roundtripped = UciRemark::parse_from(&formatted, &options)
+            .expect("failed to parse reformatted remark");
+        assert_eq!(rmk, rmk_roundtripped, "roundtripped remark is not identical");
+    }
+});
@@ -0,0 +1,25 @@
+# Seeds the fuzz corpora with every message from the UCI logs in tests/uci_logs.
+# Each input is prefixed with a flags byte (1 = chess960, 2 = wdl), as the targets expect.
+# Run from the fuzz directory.
+
+import glob
+import hashlib
+import os
+
+corpora = {">": "corpus/parse_command", "<": "corpus/parse_remark"}
+for corpus in corpora.values():
+    os.makedirs(corpus, exist_ok=True)
+
+for log in sorted(glob.glob("../tests/uci_logs/*.txt")):
+    chess960 = False
+    wdl = False
+    for line in open(log).read().splitlines():
+        kind, msg = line[0], line[1:]
+        if msg.startswith("setoption name UCI_Chess960 value "):
+            chess960 = msg.endswith(" true")
+        if msg.startswith("setoption name UCI_ShowWDL value "):
+            wdl = msg.endswith(" true")
+        data = bytes([chess960 | wdl << 1]) + msg.encode()
+        name = hashlib.sha1(data).hexdigest()
+        with open(os.path.join(corpora[kind], name), "wb") as f:
+            f.write(data)
@@ -0,0 +1,284 @@
+//! Rewriting traffic between a GUI and an engine according to a config file.
+
+use std::io;
+use std::io::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use thiserror::*;
+
+use crate::command::{UciClock, UciCommand};
+use crate::remark::{UciIdInfo, UciRemark};
+use crate::UciFormatOptions;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct UciAdapterConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UciClockField {
+    Wtime,
+    Btime,
+    Winc,
+    Binc,
+    Movetime,
+}
+
+/// The rewrites performed by a [`UciAdapter`], read from a config file with
+/// one directive per line:
+///
+/// ```text
+/// engine ./stockfish
+/// arg --some-flag
+/// set Threads = 4
+/// override Hash = 256
+/// drop Debug Log File
+/// hide EvalFile
+/// name Stockfish (adapted)
+/// clamp movetime 50 10000
+/// ```
+///
+/// `set` sends an option once the engine has sent `uciok`, `override`
+/// replaces the value of a GUI `setoption`, `drop` discards it, `hide`
+/// removes an `option` declaration and `name` replaces `id name`. `clamp`
+/// limits a `go` time in milliseconds. Lines starting with `#` are comments.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UciAdapterConfig {
+    pub engine: Option<String>,
+    pub args: Vec<String>,
+    pub set: Vec<(String, Option<String>)>,
+    pub overrides: Vec<(String, Option<String>)>,
+    pub drop: Vec<String>,
+    pub hide: Vec<String>,
+    pub name: Option<String>,
+    pub clamp: Vec<(UciClockField, u64, u64)>,
+}
+
+impl UciAdapterConfig {
+    pub fn parse(s: &str) -> Result<Self, UciAdapterConfigError> {
+        let mut config = Self::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| UciAdapterConfigError {
+                line: i + 1,
+                message: message.to_owned(),
+            };
+            let (directive, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let rest = rest.trim();
+            if rest.is_empty() {
+                return Err(error("missing argument"));
+            }
+            let option = || match rest.split_once('=') {
+                Some((name, value)) => (name.trim().to_owned(), Some(value.trim().to_owned())),
+                None => (rest.to_owned(), None),
+            };
+            match directive {
+                "engine" => config.engine = Some(rest.to_owned()),
+                "arg" => config.args.push(rest.to_owned()),
+                "set" => config.set.push(option()),
+                "override" => config.overrides.push(option()),
+                "drop" => config.drop.push(rest.to_owned()),
+                "hide" => config.hide.push(rest.to_owned()),
+                "name" => config.name = Some(rest.to_owned()),
+                "clamp" => {
+                    let args: Vec<&str> = rest.split_whitespace().collect();
+                    let [field, min, max] = args[..] else {
+                        return Err(error("expected `clamp FIELD MIN MAX`"));
+                    };
+                    let field = match field {
+                        "wtime" => UciClockField::Wtime,
+                        "btime" => UciClockField::Btime,
+                        "winc" => UciClockField::Winc,
+                        "binc" => UciClockField::Binc,
+                        "movetime" => UciClockField::Movetime,
+                        _ => return Err(error("unknown clamp field")),
+                    };
+                    let (Ok(min), Ok(max)) = (min.parse(), max.parse()) else {
+                        return Err(error("invalid clamp bound"));
+                    };
+                    if min > max {
+                        return Err(error("clamp minimum exceeds maximum"));
+                    }
+                    config.clamp.push((field, min, max));
+                }
+                _ => return Err(error("unknown directive")),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Lines to send in each direction in response to a line.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UciAdapterOutput {
+    pub to_engine: Vec<String>,
+    pub to_gui: Vec<String>,
+}
+
+/// Rewrites traffic between a GUI and an engine.
+///
+/// Each side is parsed and formatted with its own [`UciFormatOptions`], so
+/// fields the GUI did not enable, such as WDL when only the engine was set
+/// up to send it, are removed on the way back. Lines that do not parse are
+/// passed through unchanged.
+#[derive(Debug, Clone)]
+pub struct UciAdapter {
+    config: UciAdapterConfig,
+    gui_options: UciFormatOptions,
+    engine_options: UciFormatOptions,
+}
+
+impl UciAdapter {
+    pub fn new(config: UciAdapterConfig) -> Self {
+        Self {
+            config,
+            gui_options: UciFormatOptions::default(),
+            engine_options: UciFormatOptions::default(),
+        }
+    }
+
+    /// Handles a line from the GUI.
+    pub fn command(&mut self, line: &str) -> UciAdapterOutput {
+        let mut output = UciAdapterOutput::default();
+        let Ok(mut cmd) = UciCommand::parse_from(line, &self.gui_options) else {
+            output.to_engine.push(line.to_owned());
+            return output;
+        };
+        self.gui_options.update(&cmd);
+        match &mut cmd {
+            UciCommand::SetOption { name, value } => {
+                if self.config.drop.contains(name) {
+                    return output;
+                }
+                if let Some((_, v)) = self.config.overrides.iter().find(|(n, _)| n == name) {
+                    *value = v.clone();
+                }
+            }
+            UciCommand::Go(params) => {
+                for &(field, min, max) in &self.config.clamp {
+                    let clamp_clock = |clock: &mut Option<UciClock>| {
+                        if let Some(clock) = clock {
+                            clock.millis = clock.millis.clamp(min.into(), max.into());
+                        }
+                    };
+                    match field {
+                        UciClockField::Wtime => clamp_clock(&mut params.wtime),
+                        UciClockField::Btime => clamp_clock(&mut params.btime),
+                        UciClockField::Winc => clamp_clock(&mut params.winc),
+                        UciClockField::Binc => clamp_clock(&mut params.binc),
+                        UciClockField::Movetime => {
+                            if let Some(movetime) = &mut params.movetime {
+                                let (min, max) =
+                                    (Duration::from_millis(min), Duration::from_millis(max));
+                                *movetime = (*movetime).clamp(min, max);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        output.to_engine.push(self.engine_line(&cmd, line));
+        output
+    }
+
+    /// Handles a line from the engine.
+    pub fn remark(&mut self, line: &str) -> UciAdapterOutput {
+        let mut output = UciAdapterOutput::default();
+        let Ok(mut rmk) = UciRemark::parse_from(line, &self.engine_options) else {
+            output.to_gui.push(line.to_owned());
+            return output;
+        };
+        self.engine_options.update_from_remark(&rmk);
+        match &mut rmk {
+            UciRemark::Id(UciIdInfo::Name(name)) => {
+                if let Some(new_name) = &self.config.name {
+                    *name = new_name.clone();
+                }
+            }
+            UciRemark::Option { name, .. } if self.config.hide.contains(name) => return output,
+            UciRemark::UciOk => {
+                for (name, value) in self.config.set.clone() {
+                    let cmd = UciCommand::SetOption { name, value };
+                    let line = cmd.format(&self.engine_options);
+                    output.to_engine.push(self.engine_line(&cmd, &line));
+                }
+            }
+            UciRemark::Info(info) => {
+                if let Some(score) = &mut info.score {
+                    if !self.gui_options.wdl {
+                        score.wdl = None;
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.gui_options.update_from_remark(&rmk);
+        let line = rmk
+            .try_format(&self.gui_options)
+            .unwrap_or_else(|_| line.to_owned());
+        output.to_gui.push(line);
+        output
+    }
+
+    fn engine_line(&mut self, cmd: &UciCommand, line: &str) -> String {
+        self.engine_options.update(cmd);
+        cmd.try_format(&self.engine_options)
+            .unwrap_or_else(|_| line.to_owned())
+    }
+}
+
+/// Runs the adapter between a GUI and an engine until the engine closes its
+/// output. Reading from the GUI continues on a detached thread.
+pub fn run_adapter<W: Write + Send + 'static>(
+    adapter: UciAdapter,
+    gui_reader: impl BufRead + Send + 'static,
+    gui_writer: impl Write + Send + 'static,
+    engine_reader: impl BufRead,
+    engine_writer: W,
+) -> io::Result<()> {
+    let adapter = Arc::new(Mutex::new(adapter));
+    let gui_writer = Arc::new(Mutex::new(gui_writer));
+    let engine_writer = Arc::new(Mutex::new(Some(engine_writer)));
+    let send = {
+        let engine_writer = Arc::clone(&engine_writer);
+        move |output: UciAdapterOutput| -> io::Result<()> {
+            if let Some(writer) = &mut *engine_writer.lock().unwrap() {
+                write_lines(writer, &output.to_engine)?;
+            }
+            write_lines(&mut *gui_writer.lock().unwrap(), &output.to_gui)
+        }
+    };
+    {
+        let adapter = Arc::clone(&adapter);
+        let send = send.clone();
+        thread::spawn(move || {
+            for line in gui_reader.lines() {
+                let output = adapter.lock().unwrap().command(&line?);
+                send(output)?;
+            }
+            // Closing the engine's input lets it exit once the GUI is gone.
+            engine_writer.lock().unwrap().take();
+            io::Result::Ok(())
+        });
+    }
+    for line in engine_reader.lines() {
+        let output = adapter.lock().unwrap().remark(&line?);
+        send(output)?;
+    }
+    Ok(())
+}
+
+fn write_lines(writer: &mut impl Write, lines: &[String]) -> io::Result<()> {
+    for line in lines {
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,325 @@
+//! [`proptest`] strategies for every message type.
+//!
+//! Generated values are always representable, so formatting them with the
+//! same [`UciFormatOptions`] and parsing the result yields the original value.
+
+use std::time::Duration;
+
+use cozy_chess::{Board, Move, Piece, Square};
+use proptest::collection::vec;
+use proptest::option::of;
+use proptest::prelude::*;
+use proptest::sample::{select, Index};
+
+use crate::command::*;
+use crate::message::UciMessage;
+use crate::remark::*;
+use crate::{UciFormatOptions, UCI_NULL_MOVE};
+
+const RESERVED_WORDS: &[&str] = &[
+    "name", "value", "type", "default", "min", "max", "var", "string", "depth", "pv", "moves",
+];
+
+pub fn arb_format_options() -> impl Strategy<Value = UciFormatOptions> {
+    (any::<bool>(), any::<bool>(), vec(arb_string(&[]), 0..3)).prop_map(
+        |(chess960, wdl, option_names)| UciFormatOptions {
+            chess960,
+            wdl,
+            option_names,
+        },
+    )
+}
+
+pub fn arb_move() -> impl Strategy<Value = Move> {
+    (
+        select(&Square::ALL[..]),
+        select(&Square::ALL[..]),
+        of(select(
+            &[Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen][..],
+        )),
+    )
+        .prop_map(|(from, to, promotion)| Move {
+            from,
+            to,
+            promotion,
+        })
+}
+
+pub fn arb_best_move() -> impl Strategy<Value = UciBestMove> {
+    prop_oneof![
+        8 => arb_move().prop_map(UciBestMove::Move),
+        1 => Just(UciBestMove::None),
+        1 => Just(UciBestMove::Null),
+    ]
+}
+
+pub fn arb_moves() -> impl Strategy<Value = Vec<Move>> {
+    vec(
+        prop_oneof![20 => arb_move(), 1 => Just(UCI_NULL_MOVE)],
+        0..8,
+    )
+}
+
+/// Boards reached by playing random legal moves from the standard start
+/// position, or from a Chess960 start position if `chess960` is set.
+pub fn arb_board(chess960: bool) -> impl Strategy<Value = Board> {
+    let scharnagl = if chess960 { 0..960u32 } else { 518..519 };
+    (scharnagl, vec(any::<Index>(), 0..40)).prop_map(|(scharnagl, choices)| {
+        let mut board = Board::chess960_startpos(scharnagl);
+        for choice in choices {
+            let mut moves = Vec::new();
+            board.generate_moves(|mvs| {
+                moves.extend(mvs);
+                false
+            });
+            if moves.is_empty() {
+                break;
+            }
+            board.play(*choice.get(&moves));
+        }
+        board
+    })
+}
+
+fn arb_token(excluded: &'static [&'static str]) -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-zA-Z0-9_<>:;./\\\\é-]{1,8}",
+        select(RESERVED_WORDS).prop_map(str::to_owned),
+    ]
+    .prop_filter("reserved word", move |tok| {
+        !excluded.contains(&tok.as_str())
+    })
+}
+
+/// Strings of tokens separated by runs of spaces, never containing any of
+/// the `excluded` words as a token.
+pub fn arb_string(excluded: &'static [&'static str]) -> impl Strategy<Value = String> {
+    vec((arb_token(excluded), 1..4usize), 0..5).prop_map(|tokens| {
+        let mut string = String::new();
+        for (i, (token, spaces)) in tokens.into_iter().enumerate() {
+            if i > 0 {
+                string.extend(std::iter::repeat_n(' ', spaces));
+            }
+            string += &token;
+        }
+        string
+    })
+}
+
+fn arb_millis() -> impl Strategy<Value = Duration> {
+    any::<u64>().prop_map(Duration::from_millis)
+}
+
+fn arb_clock() -> impl Strategy<Value = UciClock> {
+    prop_oneof![
+        (-100_000..10_000_000i128).prop_map(UciClock::from_millis),
+        any::<i128>().prop_map(UciClock::from_millis),
+    ]
+}
+
+pub fn arb_go_params() -> impl Strategy<Value = UciGoParams> {
+    (
+        (
+            of(arb_moves()),
+            any::<bool>(),
+            of(arb_clock()),
+            of(arb_clock()),
+        ),
+        (of(arb_clock()), of(arb_clock()), of(any::<u32>())),
+        (of(any::<u32>()), of(any::<u64>()), of(any::<u32>())),
+        (of(arb_millis()), any::<bool>(), of(any::<u32>())),
+    )
+        .prop_map(
+            |(
+                (searchmoves, ponder, wtime, btime),
+                (winc, binc, movestogo),
+                (depth, nodes, mate),
+                (movetime, infinite, perft),
+            )| UciGoParams {
+                searchmoves,
+                ponder,
+                wtime,
+                btime,
+                winc,
+                binc,
+                movestogo,
+                depth,
+                nodes,
+                mate,
+                movetime,
+                infinite,
+                perft,
+            },
+        )
+}
+
+pub fn arb_command(options: &UciFormatOptions) -> impl Strategy<Value = UciCommand> {
+    let option_name = match options.option_names.is_empty() {
+        true => arb_string(&[]).boxed(),
+        false => prop_oneof![arb_string(&[]), select(options.option_names.clone())].boxed(),
+    };
+    let options = options.clone();
+    let init_pos = prop_oneof![
+        Just(UciInitPos::StartPos),
+        arb_board(options.chess960).prop_map(UciInitPos::Board),
+    ];
+    prop_oneof![
+        Just(UciCommand::Uci),
+        any::<bool>().prop_map(UciCommand::Debug),
+        Just(UciCommand::IsReady),
+        (init_pos, arb_moves())
+            .prop_map(|(init_pos, moves)| UciCommand::Position { init_pos, moves }),
+        (option_name, of(arb_string(&[])))
+            .prop_map(|(name, value)| UciCommand::SetOption { name, value })
+            .prop_filter("ambiguous option name", move |cmd| cmd
+                .try_format(&options)
+                .is_ok()),
+        Just(UciCommand::UciNewGame),
+        Just(UciCommand::Stop),
+        Just(UciCommand::PonderHit),
+        Just(UciCommand::Quit),
+        arb_go_params().prop_map(UciCommand::Go),
+    ]
+}
+
+pub fn arb_score(options: &UciFormatOptions) -> impl Strategy<Value = UciScore> {
+    let wdl = if options.wdl {
+        of(any::<(u16, u16, u16)>()).boxed()
+    } else {
+        Just(None).boxed()
+    };
+    let kind = prop_oneof![
+        Just(UciScoreKind::Exact),
+        Just(UciScoreKind::LowerBound),
+        Just(UciScoreKind::UpperBound),
+    ];
+    (of(any::<i32>()), of(any::<i32>()), wdl, kind).prop_map(|(cp, mate, wdl, kind)| UciScore {
+        cp,
+        mate,
+        wdl,
+        kind,
+    })
+}
+
+pub fn arb_score_value() -> impl Strategy<Value = UciScoreValue> {
+    prop_oneof![
+        any::<i32>().prop_map(UciScoreValue::Cp),
+        any::<i32>().prop_map(UciScoreValue::Mate),
+    ]
+}
+
+pub fn arb_info(options: &UciFormatOptions) -> impl Strategy<Value = UciInfo> {
+    let currline =
+        (of(any::<u32>()), arb_moves()).prop_map(|(cpu, moves)| UciCurrline { cpu, moves });
+    (
+        (
+            of(any::<u32>()),
+            of(any::<u32>()),
+            of(arb_millis()),
+            of(any::<u64>()),
+        ),
+        (of(arb_moves()), of(any::<u8>()), of(arb_score(options))),
+        (
+            of(arb_move()),
+            of(any::<u8>()),
+            of(any::<u16>()),
+            of(any::<u64>()),
+        ),
+        (of(any::<u64>()), of(any::<u64>()), of(any::<u16>())),
+        (of(arb_string(&[])), of(arb_moves()), of(currline)),
+    )
+        .prop_map(
+            |(
+                (depth, seldepth, time, nodes),
+                (pv, multipv, score),
+                (currmove, currmovenumber, hashfull, nps),
+                (tbhits, sbhits, cpuload),
+                (string, refutation, currline),
+            )| {
+                UciInfo {
+                    depth,
+                    seldepth,
+                    time,
+                    nodes,
+                    pv,
+                    multipv,
+                    score,
+                    currmove,
+                    currmovenumber,
+                    hashfull,
+                    nps,
+                    tbhits,
+                    sbhits,
+                    cpuload,
+                    string,
+                    refutation,
+                    currline,
+                }
+            },
+        )
+}
+
+pub fn arb_option_info() -> impl Strategy<Value = UciOptionInfo> {
+    prop_oneof![
+        any::<bool>().prop_map(|default| UciOptionInfo::Check { default }),
+        any::<(i64, i64, i64)>().prop_map(|(default, min, max)| UciOptionInfo::Spin {
+            default,
+            min,
+            max
+        }),
+        (arb_string(&["var"]), vec(arb_string(&["var"]), 0..5))
+            .prop_map(|(default, labels)| UciOptionInfo::Combo { default, labels }),
+        Just(UciOptionInfo::Button),
+        arb_string(&[]).prop_map(|default| UciOptionInfo::String { default }),
+    ]
+}
+
+pub fn arb_remark(options: &UciFormatOptions) -> impl Strategy<Value = UciRemark> {
+    let info = arb_info(options);
+    let options = options.clone();
+    prop_oneof![
+        arb_string(&[]).prop_map(|name| UciRemark::Id(UciIdInfo::Name(name))),
+        arb_string(&[]).prop_map(|author| UciRemark::Id(UciIdInfo::Author(author))),
+        Just(UciRemark::UciOk),
+        Just(UciRemark::ReadyOk),
+        (arb_best_move(), of(arb_move()))
+            .prop_map(|(mv, ponder)| UciRemark::BestMove { mv, ponder }),
+        info.prop_map(UciRemark::Info),
+        (arb_string(&[]), arb_option_info())
+            .prop_map(|(name, info)| UciRemark::Option { name, info })
+            .prop_filter("ambiguous option name", move |rmk| rmk
+                .try_format(&options)
+                .is_ok()),
+    ]
+}
+
+pub fn arb_message(options: &UciFormatOptions) -> impl Strategy<Value = UciMessage> {
+    prop_oneof![
+        arb_command(options).prop_map(UciMessage::Command),
+        arb_remark(options).prop_map(UciMessage::Remark),
+    ]
+}
+
+macro_rules! impl_arbitrary {
+    ($($type:ty, $params:ty => |$options:ident| $strategy:expr;)*) => {$(
+        impl Arbitrary for $type {
+            type Parameters = $params;
+            type Strategy = BoxedStrategy<Self>;
+
+            fn arbitrary_with($options: Self::Parameters) -> Self::Strategy {
+                $strategy.boxed()
+            }
+        }
+    )*};
+}
+
+impl_arbitrary! {
+    UciCommand, UciFormatOptions => |options| arb_command(&options);
+    UciGoParams, () => |_options| arb_go_params();
+    UciRemark, UciFormatOptions => |options| arb_remark(&options);
+    UciMessage, UciFormatOptions => |options| arb_message(&options);
+    UciInfo, UciFormatOptions => |options| arb_info(&options);
+    UciScore, UciFormatOptions => |options| arb_score(&options);
+    UciScoreValue, () => |_options| arb_score_value();
+    UciOptionInfo, () => |_options| arb_option_info();
+}
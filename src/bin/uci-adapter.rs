@@ -0,0 +1,63 @@
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{exit, Command, Stdio};
+
+use cozy_uci::adapter::{run_adapter, UciAdapter, UciAdapterConfig};
+
+const USAGE: &str = "usage: uci-adapter [CONFIG]
+
+Without arguments, the config is read from uci-adapter.cfg next to the
+executable. Relative engine paths are resolved against the config's directory.";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let config_path = match (args.next(), args.next()) {
+        (None, _) => {
+            let exe = std::env::current_exe().unwrap_or_else(|err| fail(&err.to_string()));
+            exe.with_file_name("uci-adapter.cfg")
+        }
+        (Some(arg), None) if arg != "--help" && arg != "-h" => PathBuf::from(arg),
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+    let contents = std::fs::read_to_string(&config_path).unwrap_or_else(|err| {
+        fail(&format!(
+            "failed to read {}: {}",
+            config_path.display(),
+            err
+        ))
+    });
+    let config = UciAdapterConfig::parse(&contents)
+        .unwrap_or_else(|err| fail(&format!("{}: {}", config_path.display(), err)));
+    let Some(engine) = &config.engine else {
+        fail(&format!("{}: missing `engine`", config_path.display()));
+    };
+    let dir = config_path.parent().unwrap_or(Path::new("."));
+    let engine = dir.join(engine);
+    let mut child = Command::new(&engine)
+        .args(&config.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|err| fail(&format!("failed to start {}: {}", engine.display(), err)));
+
+    let result = run_adapter(
+        UciAdapter::new(config),
+        BufReader::new(io::stdin()),
+        io::stdout(),
+        BufReader::new(child.stdout.take().unwrap()),
+        child.stdin.take().unwrap(),
+    );
+    let status = child.wait();
+    if let Err(err) = result {
+        fail(&format!("adapter failed: {}", err));
+    }
+    exit(status.ok().and_then(|s| s.code()).unwrap_or(1));
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(2);
+}
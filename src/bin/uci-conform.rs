@@ -0,0 +1,56 @@
+use std::process::{exit, Command};
+use std::time::Duration;
+
+use cozy_uci::conform::{check_conformance, UciConformConfig};
+use cozy_uci::engine::UciEngine;
+
+const USAGE: &str = "usage: uci-conform [--timeout MS] [--search-time MS] ENGINE [ARGS...]";
+
+fn main() {
+    let mut config = UciConformConfig::default();
+    let mut args = std::env::args().skip(1);
+    let engine_path = loop {
+        let arg = args.next().unwrap_or_else(|| usage());
+        let field = match arg.as_str() {
+            "--timeout" => &mut config.timeout,
+            "--search-time" => &mut config.search_time,
+            "--help" | "-h" => usage(),
+            _ => break arg,
+        };
+        let millis = args.next().and_then(|ms| ms.parse().ok());
+        *field = Duration::from_millis(millis.unwrap_or_else(|| usage()));
+    };
+
+    let mut engine = match UciEngine::spawn(Command::new(&engine_path).args(args)) {
+        Ok(engine) => engine,
+        Err(err) => {
+            eprintln!("failed to start {}: {}", engine_path, err);
+            exit(2);
+        }
+    };
+    let checks = check_conformance(&mut engine, &config);
+    let _ = engine.quit(config.timeout);
+
+    let mut failed = 0;
+    for check in &checks {
+        match &check.result {
+            Ok(()) => println!("PASS  {}", check.name),
+            Err(reason) => {
+                failed += 1;
+                println!("FAIL  {}", check.name);
+                for line in reason.lines() {
+                    println!("      {}", line);
+                }
+            }
+        }
+    }
+    println!("{}/{} checks passed", checks.len() - failed, checks.len());
+    if failed > 0 {
+        exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(2);
+}
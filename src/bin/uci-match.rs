@@ -0,0 +1,148 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::{exit, Command};
+use std::time::{Duration, SystemTime};
+
+use cozy_uci::command::{UciCommand, UciInitPos};
+use cozy_uci::engine::UciEngine;
+use cozy_uci::game::{pgn_date, play_game, UciGameResult, UciGameSettings};
+
+const USAGE: &str = "usage: uci-match [--games N] [--tc BASE+INC] [--margin MS] [--fen FEN] \
+                     [--chess960] [--option NAME=VALUE]... [--pgn FILE] ENGINE1 ENGINE2
+
+Each ENGINE is a command line, split on whitespace. The engines swap
+colours after every game, and the games are appended to the PGN file,
+uci-match.pgn by default.";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Player {
+    name: String,
+    engine: UciEngine,
+}
+
+fn main() {
+    let mut games = 2;
+    let mut settings = UciGameSettings::default();
+    let mut options = Vec::new();
+    let mut pgn_path = "uci-match.pgn".to_owned();
+    let mut commands = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--games" => games = parse_or_usage(&value()),
+            "--tc" => settings.time_control = parse_or_usage(&value()),
+            "--margin" => settings.margin = Duration::from_millis(parse_or_usage(&value())),
+            "--fen" => settings.init_pos = UciInitPos::Board(parse_or_usage(&value())),
+            "--chess960" => settings.chess960 = true,
+            "--option" => {
+                let value = value();
+                let Some((name, value)) = value.split_once('=') else {
+                    usage();
+                };
+                options.push((name.to_owned(), value.to_owned()));
+            }
+            "--pgn" => pgn_path = value(),
+            "--help" | "-h" => usage(),
+            _ => commands.push(arg),
+        }
+    }
+    if settings.chess960 {
+        options.push(("UCI_Chess960".to_owned(), "true".to_owned()));
+    }
+    let [first, second] = &commands[..] else {
+        usage();
+    };
+    let mut players = [start(first, &options), start(second, &options)];
+    if players[0].name == players[1].name {
+        players[1].name += " (2)";
+    }
+
+    let date = pgn_date(SystemTime::now());
+    let mut wins = [0, 0];
+    let mut draws = 0;
+    for round in 1..=games {
+        let (white, black) = match round % 2 {
+            1 => (0, 1),
+            _ => (1, 0),
+        };
+        let [p0, p1] = &mut players;
+        let (w, b) = match white {
+            0 => (p0, p1),
+            _ => (p1, p0),
+        };
+        let game = play_game(&mut w.engine, &mut b.engine, &settings);
+        match game.result {
+            UciGameResult::WhiteWins => wins[white] += 1,
+            UciGameResult::BlackWins => wins[black] += 1,
+            UciGameResult::Draw => draws += 1,
+        }
+        let round_tag = round.to_string();
+        let pgn = game.to_pgn(&[
+            ("Event", "uci-match"),
+            ("Site", "?"),
+            ("Date", &date),
+            ("Round", &round_tag),
+            ("White", &w.name),
+            ("Black", &b.name),
+        ]);
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&pgn_path)
+            .and_then(|mut file| file.write_all(pgn.as_bytes()));
+        if let Err(err) = written {
+            eprintln!("failed to write {}: {}", pgn_path, err);
+            exit(2);
+        }
+        println!(
+            "Game {}: {} vs {}: {} {{{}}}",
+            round,
+            w.name,
+            b.name,
+            game.result,
+            game.reason()
+        );
+        println!(
+            "Score of {} vs {}: {} - {} - {}",
+            players[0].name, players[1].name, wins[0], wins[1], draws
+        );
+    }
+    for player in players {
+        let _ = player.engine.quit(Duration::from_secs(1));
+    }
+}
+
+fn start(command: &str, options: &[(String, String)]) -> Player {
+    let mut words = command.split_whitespace();
+    let program = words.next().unwrap_or_else(|| usage());
+    let fail = |err: &dyn std::fmt::Display| -> ! {
+        eprintln!("failed to start {}: {}", program, err);
+        exit(2);
+    };
+    let mut engine =
+        UciEngine::spawn(Command::new(program).args(words)).unwrap_or_else(|err| fail(&err));
+    let name = engine
+        .handshake(TIMEOUT)
+        .unwrap_or_else(|err| fail(&err))
+        .unwrap_or_else(|| program.to_owned());
+    for (name, value) in options {
+        let cmd = UciCommand::SetOption {
+            name: name.clone(),
+            value: Some(value.clone()),
+        };
+        engine.send(&cmd).unwrap_or_else(|err| fail(&err));
+    }
+    engine.wait_ready(TIMEOUT).unwrap_or_else(|err| fail(&err));
+    Player { name, engine }
+}
+
+fn parse_or_usage<T: std::str::FromStr>(s: &str) -> T {
+    s.parse().unwrap_or_else(|_| usage())
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(2);
+}
@@ -0,0 +1,111 @@
+use std::process::{exit, Command};
+use std::time::Duration;
+
+use cozy_uci::command::{UciCommand, UciInitPos};
+use cozy_uci::engine::{UciEngine, UciEngineError};
+use cozy_uci::perft::{find_perft_mismatch, UciPerftMismatchKind};
+
+const USAGE: &str = "usage: uci-perft [--timeout MS] [--fen FEN] [--moves MOVES] [--chess960] \
+                     DEPTH ENGINE [ARGS...]";
+
+fn main() {
+    let mut timeout = Duration::from_secs(60);
+    let mut init_pos = UciInitPos::StartPos;
+    let mut moves = Vec::new();
+    let mut chess960 = false;
+    let mut args = std::env::args().skip(1);
+    let depth = loop {
+        let arg = args.next().unwrap_or_else(|| usage());
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--timeout" => timeout = Duration::from_millis(parse_or_usage(&value())),
+            "--fen" => init_pos = UciInitPos::Board(parse_or_usage(&value())),
+            "--moves" => {
+                moves = value().split_whitespace().map(parse_or_usage).collect();
+            }
+            "--chess960" => chess960 = true,
+            "--help" | "-h" => usage(),
+            _ => break parse_or_usage(&arg),
+        }
+    };
+    if depth == 0 {
+        usage();
+    }
+    let engine_path = args.next().unwrap_or_else(|| usage());
+
+    let mut engine = match UciEngine::spawn(Command::new(&engine_path).args(args)) {
+        Ok(engine) => engine,
+        Err(err) => {
+            eprintln!("failed to start {}: {}", engine_path, err);
+            exit(2);
+        }
+    };
+    if let Err(err) = set_up(&mut engine, chess960, timeout) {
+        eprintln!("engine failed to start up: {}", err);
+        exit(2);
+    }
+    let result = find_perft_mismatch(&mut engine, &init_pos, &moves, depth, timeout);
+    let _ = engine.quit(Duration::from_secs(1));
+
+    let mismatch = match result {
+        Ok(None) => {
+            println!("perft {} matches", depth);
+            return;
+        }
+        Ok(Some(mismatch)) => mismatch,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(2);
+        }
+    };
+    let line: Vec<String> = mismatch.moves.iter().map(|mv| mv.to_string()).collect();
+    println!(
+        "mismatch at perft {} after [{}]",
+        mismatch.depth,
+        line.join(" ")
+    );
+    match chess960 {
+        false => println!("fen {}", mismatch.board),
+        true => println!("fen {:#}", mismatch.board),
+    }
+    match mismatch.kind {
+        UciPerftMismatchKind::Missing(mv) => println!("engine is missing legal move {}", mv),
+        UciPerftMismatchKind::Illegal(mv) => println!("engine generates illegal move {}", mv),
+        UciPerftMismatchKind::Duplicate(mv) => println!("engine lists {} more than once", mv),
+        UciPerftMismatchKind::Nodes {
+            mv,
+            expected,
+            actual,
+        } => println!(
+            "{}: expected {} nodes, engine counts {}",
+            mv, expected, actual
+        ),
+        UciPerftMismatchKind::Total { expected, actual } => {
+            println!(
+                "expected {} nodes in total, engine reports {}",
+                expected, actual
+            )
+        }
+    }
+    exit(1);
+}
+
+fn set_up(engine: &mut UciEngine, chess960: bool, timeout: Duration) -> Result<(), UciEngineError> {
+    engine.handshake(timeout)?;
+    if chess960 {
+        engine.send(&UciCommand::SetOption {
+            name: "UCI_Chess960".to_owned(),
+            value: Some("true".to_owned()),
+        })?;
+    }
+    engine.wait_ready(timeout)
+}
+
+fn parse_or_usage<T: std::str::FromStr>(s: &str) -> T {
+    s.parse().unwrap_or_else(|_| usage())
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(2);
+}
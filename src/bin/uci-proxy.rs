@@ -0,0 +1,118 @@
+use std::fs::OpenOptions;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{exit, Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cozy_uci::proxy::{run_proxy, UciTranscript};
+
+const USAGE: &str = "usage: uci-proxy [--log FILE] ENGINE [ARGS...]
+
+Without arguments, the engine is read from uci-proxy.cfg next to the
+executable, with lines `engine PATH`, `arg ARG` and `log FILE`.";
+
+struct Config {
+    engine: PathBuf,
+    args: Vec<String>,
+    log: PathBuf,
+}
+
+fn main() {
+    let config = match std::env::args().len() {
+        1 => read_config_file(),
+        _ => parse_args(),
+    };
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&config.log)
+        .unwrap_or_else(|err| fail(&format!("failed to open {}", config.log.display()), err));
+    let mut child = Command::new(&config.engine)
+        .args(&config.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|err| fail(&format!("failed to start {}", config.engine.display()), err));
+
+    let mut transcript = UciTranscript::new(log);
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |t| t.as_secs());
+    let _ = transcript.comment(&format!(
+        "session started at {} running {}",
+        started,
+        config.engine.display()
+    ));
+    let result = run_proxy(
+        BufReader::new(io::stdin()),
+        io::stdout(),
+        BufReader::new(child.stdout.take().unwrap()),
+        child.stdin.take().unwrap(),
+        transcript,
+    );
+    let status = child.wait();
+    if let Err(err) = result {
+        fail("proxy failed", err);
+    }
+    exit(status.ok().and_then(|s| s.code()).unwrap_or(1));
+}
+
+fn parse_args() -> Config {
+    let mut log = PathBuf::from("uci-proxy.log");
+    let mut args = std::env::args().skip(1);
+    let engine = loop {
+        let arg = args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--log" => log = args.next().unwrap_or_else(|| usage()).into(),
+            "--help" | "-h" => usage(),
+            _ => break arg.into(),
+        }
+    };
+    Config {
+        engine,
+        args: args.collect(),
+        log,
+    }
+}
+
+fn read_config_file() -> Config {
+    let exe = std::env::current_exe().unwrap_or_else(|err| fail("failed to find executable", err));
+    let dir = exe.parent().unwrap_or(Path::new("."));
+    let path = dir.join("uci-proxy.cfg");
+    let contents = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| fail(&format!("failed to read {}", path.display()), err));
+    let mut engine = None;
+    let mut args = Vec::new();
+    let mut log = dir.join("uci-proxy.log");
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        let value = value.trim();
+        match key {
+            "engine" => engine = Some(dir.join(value)),
+            "arg" => args.push(value.to_owned()),
+            "log" => log = dir.join(value),
+            _ => {
+                eprintln!("{}: unknown key `{}`", path.display(), key);
+                exit(2);
+            }
+        }
+    }
+    let Some(engine) = engine else {
+        eprintln!("{}: missing `engine`", path.display());
+        exit(2);
+    };
+    Config { engine, args, log }
+}
+
+fn fail(context: &str, err: io::Error) -> ! {
+    eprintln!("{}: {}", context, err);
+    exit(2);
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(2);
+}
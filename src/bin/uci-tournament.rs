@@ -0,0 +1,170 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::process::{exit, Command};
+use std::time::{Duration, SystemTime};
+
+use cozy_uci::command::{UciCommand, UciInitPos};
+use cozy_uci::engine::{UciEngine, UciEngineError};
+use cozy_uci::game::{pgn_date, UciGameSettings};
+use cozy_uci::tournament::*;
+
+const USAGE: &str = "usage: uci-tournament [--format round-robin|gauntlet|swiss:ROUNDS] \
+                     [--games N] [--concurrency N] [--tc BASE+INC] [--margin MS] [--fen FEN] \
+                     [--chess960] [--option NAME=VALUE]... [--state FILE] [--pgn FILE] \
+                     ENGINE...
+
+Each ENGINE is a command line, split on whitespace. Each pairing plays
+N games, 2 by default, alternating colours. In a gauntlet the first
+engine plays all the others.
+
+Finished games are recorded in the state file, uci-tournament.state by
+default, and a tournament that is run again with the same format, games
+and engines resumes from it. Games are appended to the PGN file,
+uci-tournament.pgn by default.";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn main() {
+    let mut format = UciTournamentFormat::RoundRobin;
+    let mut games = 2;
+    let mut concurrency = 1;
+    let mut settings = UciGameSettings::default();
+    let mut options = Vec::new();
+    let mut state_path = "uci-tournament.state".to_owned();
+    let mut pgn_path = "uci-tournament.pgn".to_owned();
+    let mut commands = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--format" => format = parse_or_usage(&value()),
+            "--games" => games = parse_or_usage(&value()),
+            "--concurrency" => concurrency = parse_or_usage(&value()),
+            "--tc" => settings.time_control = parse_or_usage(&value()),
+            "--margin" => settings.margin = Duration::from_millis(parse_or_usage(&value())),
+            "--fen" => settings.init_pos = UciInitPos::Board(parse_or_usage(&value())),
+            "--chess960" => settings.chess960 = true,
+            "--option" => {
+                let value = value();
+                let Some((name, value)) = value.split_once('=') else {
+                    usage();
+                };
+                options.push((name.to_owned(), value.to_owned()));
+            }
+            "--state" => state_path = value(),
+            "--pgn" => pgn_path = value(),
+            "--help" | "-h" => usage(),
+            _ => commands.push(arg),
+        }
+    }
+    if commands.len() < 2
+        || commands
+            .iter()
+            .any(|c| c.split_whitespace().next().is_none())
+    {
+        usage();
+    }
+    if settings.chess960 {
+        options.push(("UCI_Chess960".to_owned(), "true".to_owned()));
+    }
+    let config = UciTournamentConfig {
+        players: commands,
+        format,
+        games_per_pairing: games,
+        settings,
+        concurrency,
+    };
+
+    let mut state = match fs::read_to_string(&state_path) {
+        Ok(s) => UciTournamentState::parse(&s, &config).unwrap_or_else(|err| {
+            eprintln!("{}: {}", state_path, err);
+            exit(2);
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            fs::write(&state_path, UciTournamentState::header(&config))
+                .unwrap_or_else(|err| fail_write(&state_path, err));
+            UciTournamentState::default()
+        }
+        Err(err) => {
+            eprintln!("failed to read {}: {}", state_path, err);
+            exit(2);
+        }
+    };
+    if !state.games.is_empty() {
+        println!("Resuming with {} finished games", state.games.len());
+    }
+    let mut log = OpenOptions::new()
+        .append(true)
+        .open(&state_path)
+        .unwrap_or_else(|err| fail_write(&state_path, err));
+
+    let date = pgn_date(SystemTime::now());
+    let spawn = |player: usize| start(&config.players[player], &options);
+    let result = run_tournament(&config, &mut state, &mut log, &spawn, |record, game| {
+        let pairing = record.pairing;
+        let (white, black) = (
+            &config.players[pairing.white],
+            &config.players[pairing.black],
+        );
+        let round = pairing.round.to_string();
+        let pgn = game.to_pgn(&[
+            ("Event", "uci-tournament"),
+            ("Site", "?"),
+            ("Date", &date),
+            ("Round", &round),
+            ("White", white),
+            ("Black", black),
+        ]);
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&pgn_path)
+            .and_then(|mut file| file.write_all(pgn.as_bytes()));
+        if let Err(err) = written {
+            fail_write(&pgn_path, err);
+        }
+        println!(
+            "Game {} (round {}): {} vs {}: {} {{{}}}",
+            pairing.seq + 1,
+            pairing.round,
+            white,
+            black,
+            game.result,
+            game.reason()
+        );
+    });
+    if let Err(err) = result {
+        fail_write(&state_path, err);
+    }
+    println!();
+    print!("{}", UciCrosstable::new(&config.players, &state));
+}
+
+fn start(command: &str, options: &[(String, String)]) -> Result<UciEngine, UciEngineError> {
+    let mut words = command.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let mut engine = UciEngine::spawn(Command::new(program).args(words))?;
+    engine.handshake(TIMEOUT)?;
+    for (name, value) in options {
+        engine.send(&UciCommand::SetOption {
+            name: name.clone(),
+            value: Some(value.clone()),
+        })?;
+    }
+    engine.wait_ready(TIMEOUT)?;
+    Ok(engine)
+}
+
+fn fail_write(path: &str, err: io::Error) -> ! {
+    eprintln!("failed to write {}: {}", path, err);
+    exit(2);
+}
+
+fn parse_or_usage<T: std::str::FromStr>(s: &str) -> T {
+    s.parse().unwrap_or_else(|_| usage())
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(2);
+}
@@ -0,0 +1,44 @@
+use std::io::{self, BufReader};
+use std::process::{exit, Command, Stdio};
+
+use cozy_uci::uci2xboard::{run_uci2xboard, Uci2Xboard};
+
+const USAGE: &str = "usage: uci2xboard ENGINE [ARGS...]
+
+Runs a UCI engine, presenting it to an xboard GUI on stdin and stdout.";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let engine = match args.next() {
+        Some(arg) if arg != "--help" && arg != "-h" => arg,
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+    let mut child = Command::new(&engine)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|err| fail(&format!("failed to start {}: {}", engine, err)));
+
+    let result = run_uci2xboard(
+        Uci2Xboard::new(),
+        BufReader::new(io::stdin()),
+        io::stdout(),
+        BufReader::new(child.stdout.take().unwrap()),
+        child.stdin.take().unwrap(),
+    );
+    // Closing its input lets the engine exit if it missed `quit`.
+    let status = child.wait();
+    if let Err(err) = result {
+        fail(&format!("adapter failed: {}", err));
+    }
+    exit(status.ok().and_then(|s| s.code()).unwrap_or(1));
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(2);
+}
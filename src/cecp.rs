@@ -0,0 +1,232 @@
+//! Pieces of the Chess Engine Communication Protocol (CECP), spoken by
+//! xboard and WinBoard, shared by the adapters between it and UCI.
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use cozy_chess::util::parse_san_move;
+use cozy_chess::{Board, File, Move, Rank, Square};
+
+use crate::moves::{play_uci_move, to_uci_move};
+use crate::remark::{UciOptionInfo, UciScoreValue};
+
+/// Scores at or beyond this magnitude are mates, written as `100000 + N`
+/// for mate in `N` moves and `-100000 - N` for being mated in `N` moves.
+pub const CECP_MATE_SCORE: i32 = 100000;
+
+/// Converts a score to the centipawns of CECP thinking output.
+pub fn cecp_score(value: UciScoreValue) -> i32 {
+    match value {
+        UciScoreValue::Cp(cp) => cp.clamp(1 - CECP_MATE_SCORE, CECP_MATE_SCORE - 1),
+        UciScoreValue::Mate(moves) if moves > 0 => CECP_MATE_SCORE.saturating_add(moves),
+        UciScoreValue::Mate(moves) => (-CECP_MATE_SCORE).saturating_add(moves),
+    }
+}
+
+/// Converts the centipawns of CECP thinking output back to a score.
+pub fn cecp_score_value(score: i32) -> UciScoreValue {
+    match score {
+        s if s >= CECP_MATE_SCORE => UciScoreValue::Mate(s - CECP_MATE_SCORE),
+        s if s <= -CECP_MATE_SCORE => UciScoreValue::Mate(s + CECP_MATE_SCORE),
+        s => UciScoreValue::Cp(s),
+    }
+}
+
+/// A line of thinking output: `ply score time nodes pv`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CecpThinking {
+    pub depth: u32,
+    pub score: i32,
+    pub time: Duration,
+    pub nodes: u64,
+    pub pv: String,
+}
+
+impl CecpThinking {
+    /// Parses a line of thinking output. Extra fields after the node count
+    /// are ignored if the PV is separated from them by a tab.
+    pub fn parse_from(s: &str) -> Option<Self> {
+        let (fields, pv) = match s.split_once('\t') {
+            Some((fields, pv)) => (fields, Some(pv)),
+            None => (s, None),
+        };
+        let mut fields = fields.split_whitespace();
+        let depth = fields.next()?.trim_end_matches(['.', '&']).parse().ok()?;
+        let score = fields.next()?.parse().ok()?;
+        let centis: u64 = fields.next()?.parse().ok()?;
+        let nodes = fields.next()?.parse().ok()?;
+        let pv = match pv {
+            Some(pv) => pv.trim().to_owned(),
+            None => fields.collect::<Vec<_>>().join(" "),
+        };
+        Some(Self {
+            depth,
+            score,
+            time: Duration::from_millis(centis * 10),
+            nodes,
+            pv,
+        })
+    }
+}
+
+impl Display for CecpThinking {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.depth,
+            self.score,
+            self.time.as_millis() / 10,
+            self.nodes,
+            self.pv
+        )
+    }
+}
+
+/// Formats the value of `feature option="..."` for a UCI option.
+pub fn cecp_feature_option(name: &str, info: &UciOptionInfo) -> String {
+    match info {
+        UciOptionInfo::Check { default } => format!("{} -check {}", name, *default as u8),
+        UciOptionInfo::Spin { default, min, max } => {
+            format!("{} -spin {} {} {}", name, default, min, max)
+        }
+        UciOptionInfo::Combo { default, labels } => {
+            let choices: Vec<String> = labels
+                .iter()
+                .map(|label| match label == default {
+                    true => format!("*{}", label),
+                    false => label.clone(),
+                })
+                .collect();
+            format!("{} -combo {}", name, choices.join(" /// "))
+        }
+        UciOptionInfo::Button => format!("{} -button", name),
+        UciOptionInfo::String { default } => format!("{} -string {}", name, default),
+    }
+}
+
+/// Parses the value of `feature option="..."` into a UCI option. Slider
+/// options become spins, and `-file` and `-path` options become strings.
+pub fn parse_cecp_feature_option(s: &str) -> Option<(String, UciOptionInfo)> {
+    const KINDS: [&str; 10] = [
+        "check", "spin", "slider", "combo", "button", "save", "reset", "string", "file", "path",
+    ];
+    let (name, kind, args) = s.match_indices(" -").find_map(|(i, _)| {
+        let rest = &s[i + 2..];
+        let (kind, args) = rest.split_once(' ').unwrap_or((rest, ""));
+        KINDS
+            .contains(&kind)
+            .then(|| (s[..i].trim(), kind, args.trim()))
+    })?;
+    if name.is_empty() {
+        return None;
+    }
+    let info = match kind {
+        "check" => UciOptionInfo::Check {
+            default: match args {
+                "0" => false,
+                "1" => true,
+                _ => return None,
+            },
+        },
+        "spin" | "slider" => {
+            let args: Vec<i64> = args
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .ok()?;
+            let [default, min, max] = args[..] else {
+                return None;
+            };
+            UciOptionInfo::Spin { default, min, max }
+        }
+        "combo" => {
+            let mut default = None;
+            let labels: Vec<String> = args
+                .split("///")
+                .map(|label| {
+                    let label = label.trim();
+                    match label.strip_prefix('*').map(str::trim) {
+                        Some(label) => {
+                            default = Some(label.to_owned());
+                            label.to_owned()
+                        }
+                        None => label.to_owned(),
+                    }
+                })
+                .collect();
+            UciOptionInfo::Combo {
+                default: default.unwrap_or_else(|| labels[0].clone()),
+                labels,
+            }
+        }
+        "button" | "save" | "reset" => UciOptionInfo::Button,
+        _ => UciOptionInfo::String {
+            default: args.to_owned(),
+        },
+    };
+    Some((name.to_owned(), info))
+}
+
+/// Parses the `NAME=VALUE` pairs of a `feature` line, where values may be
+/// quoted.
+pub fn parse_cecp_features(s: &str) -> Vec<(String, String)> {
+    let mut features = Vec::new();
+    let mut rest = s.trim_start();
+    while let Some((name, value)) = rest.split_once('=') {
+        let (value, next) = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => value.split_once(' ').unwrap_or((value, "")),
+        };
+        features.push((name.trim().to_owned(), value.to_owned()));
+        rest = next.trim_start();
+    }
+    features
+}
+
+/// Parses a move in coordinate notation or SAN, or castling as
+/// `O-O`/`O-O-O`, into a legal move as written in UCI.
+pub fn parse_cecp_move(board: &Board, text: &str, chess960: bool) -> Option<Move> {
+    let color = board.side_to_move();
+    let rank = Rank::First.relative_to(color);
+    let king = board.king(color);
+    let rights = board.castle_rights(color);
+    let castle = |file: Option<File>, standard: File| {
+        let to = match chess960 {
+            true => Square::new(file?, rank),
+            false => Square::new(standard, rank),
+        };
+        Some(Move {
+            from: king,
+            to,
+            promotion: None,
+        })
+    };
+    let text = text.trim_end_matches(['+', '#', '!', '?']);
+    let mv = match text {
+        "O-O" | "0-0" => castle(rights.short, File::G)?,
+        "O-O-O" | "0-0-0" => castle(rights.long, File::C)?,
+        text => match text.parse() {
+            Ok(mv) => mv,
+            Err(_) => to_uci_move(board, parse_san_move(board, text).ok()?, chess960),
+        },
+    };
+    let mut child = board.clone();
+    play_uci_move(&mut child, mv, chess960).ok()?;
+    Some(mv)
+}
+
+/// Writes a move as CECP expects, with castling as `O-O` in Chess960.
+pub fn format_cecp_move(board: &Board, mv: Move, chess960: bool) -> String {
+    let color = board.side_to_move();
+    if chess960 && board.colors(color).has(mv.to) {
+        let king = board.king(color);
+        if mv.from == king {
+            return match mv.to.file() > king.file() {
+                true => "O-O".to_owned(),
+                false => "O-O-O".to_owned(),
+            };
+        }
+    }
+    mv.to_string()
+}
@@ -1,3 +1,6 @@
+use std::fmt::{Display, Formatter};
+use std::num::{IntErrorKind, ParseIntError};
+use std::str::FromStr;
 use std::time::Duration;
 
 use cozy_chess::{Board, Move};
@@ -17,20 +20,76 @@ impl From<UciInitPos> for Board {
     }
 }
 
+/// A clock value in milliseconds as sent by the GUI.
+///
+/// GUIs may send negative times after a flag fall or lag compensation, so the
+/// value is kept as written. Values too large to represent saturate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct UciClock {
+    pub millis: i128,
+}
+
+impl UciClock {
+    pub fn from_millis(millis: i128) -> Self {
+        Self { millis }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.millis < 0
+    }
+
+    /// The clock as a [`Duration`], clamping negative values to zero and
+    /// saturating at [`Duration::MAX`].
+    pub fn to_duration(&self) -> Duration {
+        let millis = self.millis.max(0) as u128;
+        match u64::try_from(millis / 1000) {
+            Ok(secs) => Duration::new(secs, (millis % 1000) as u32 * 1_000_000),
+            Err(_) => Duration::MAX,
+        }
+    }
+}
+
+impl From<Duration> for UciClock {
+    fn from(value: Duration) -> Self {
+        Self::from_millis(value.as_millis() as i128)
+    }
+}
+
+impl FromStr for UciClock {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(millis) => Ok(Self::from_millis(millis)),
+            Err(e) if *e.kind() == IntErrorKind::PosOverflow => Ok(Self::from_millis(i128::MAX)),
+            Err(e) if *e.kind() == IntErrorKind::NegOverflow => Ok(Self::from_millis(i128::MIN)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Display for UciClock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.millis)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct UciGoParams {
     pub searchmoves: Option<Vec<Move>>,
     pub ponder: bool,
-    pub wtime: Option<Duration>,
-    pub btime: Option<Duration>,
-    pub winc: Option<Duration>,
-    pub binc: Option<Duration>,
+    pub wtime: Option<UciClock>,
+    pub btime: Option<UciClock>,
+    pub winc: Option<UciClock>,
+    pub binc: Option<UciClock>,
     pub movestogo: Option<u32>,
     pub depth: Option<u32>,
     pub nodes: Option<u64>,
     pub mate: Option<u32>,
     pub movetime: Option<Duration>,
     pub infinite: bool,
+    /// Count leaf nodes instead of searching, as supported by many engines.
+    pub perft: Option<u32>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -51,4 +110,10 @@ pub enum UciCommand {
     PonderHit,
     Quit,
     Go(UciGoParams),
+    /// A command registered with [`UciRegistry`](crate::registry::UciRegistry).
+    Custom(Box<dyn crate::registry::UciExtension>),
+    /// A de facto extension command. The variant always exists so that the
+    /// `extensions` feature stays additive, but it is only parsed with the
+    /// feature enabled.
+    Ext(crate::extensions::UciExtCommand),
 }
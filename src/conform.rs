@@ -0,0 +1,280 @@
+use std::time::{Duration, Instant};
+
+use crate::command::{UciCommand, UciGoParams, UciInitPos};
+use crate::engine::{UciEngine, UciEngineError};
+use crate::remark::{UciIdInfo, UciRemark};
+use crate::validate::UciMoveValidator;
+
+const NEW_GAME_FEN: &str = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UciConformConfig {
+    /// How long to wait for a response the engine is required to send.
+    pub timeout: Duration,
+    /// How long to let the engine search before checking on it.
+    pub search_time: Duration,
+}
+
+impl Default for UciConformConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            search_time: Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UciConformCheck {
+    pub name: &'static str,
+    pub result: Result<(), String>,
+}
+
+/// Runs every conformance check against a freshly started engine.
+pub fn check_conformance(
+    engine: &mut UciEngine,
+    config: &UciConformConfig,
+) -> Vec<UciConformCheck> {
+    let mut checker = Checker {
+        engine,
+        config,
+        parse_failures: Vec::new(),
+        searching: false,
+    };
+    let mut checks = Vec::new();
+    macro_rules! run_checks {
+        ($($name:literal => $check:ident,)*) => {$(
+            let result = checker.$check();
+            checker.settle();
+            checks.push(UciConformCheck { name: $name, result });
+        )*};
+    }
+    run_checks! {
+        "uciok after id and option" => check_uci,
+        "isready answered" => check_isready,
+        "go infinite does not return" => check_go_infinite,
+        "isready answered during search" => check_isready_during_search,
+        "stop yields exactly one bestmove" => check_stop,
+        "ponderhit handled" => check_ponderhit,
+        "ucinewgame then position fen" => check_new_game,
+    }
+    let result = match checker.parse_failures.as_slice() {
+        [] => Ok(()),
+        failures => Err(failures.join("\n")),
+    };
+    checks.push(UciConformCheck {
+        name: "every remark parses",
+        result,
+    });
+    checks
+}
+
+struct Checker<'c> {
+    engine: &'c mut UciEngine,
+    config: &'c UciConformConfig,
+    parse_failures: Vec<String>,
+    /// Whether a search has been started without a bestmove since.
+    searching: bool,
+}
+
+impl Checker<'_> {
+    fn send(&mut self, cmd: UciCommand) -> Result<(), String> {
+        self.engine.send(&cmd).map_err(|e| {
+            format!(
+                "failed to send `{}`: {}",
+                cmd.format(&self.engine.options),
+                e
+            )
+        })
+    }
+
+    fn recv_within(&mut self, timeout: Duration) -> Result<Option<UciRemark>, String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.engine.recv(timeout) {
+                Ok(rmk) => {
+                    if let UciRemark::BestMove { .. } = rmk {
+                        self.searching = false;
+                    }
+                    return Ok(Some(rmk));
+                }
+                Err(UciEngineError::Timeout) => return Ok(None),
+                Err(UciEngineError::Parse { line, error }) => {
+                    self.parse_failures.push(format!("{:?}: {}", line, error))
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+
+    fn recv(&mut self, waiting_for: &str) -> Result<UciRemark, String> {
+        self.recv_within(self.config.timeout)?
+            .ok_or_else(|| format!("timed out waiting for {}", waiting_for))
+    }
+
+    fn sync(&mut self) -> Result<Vec<UciRemark>, String> {
+        self.send(UciCommand::IsReady)?;
+        let mut remarks = Vec::new();
+        loop {
+            match self.recv("readyok")? {
+                UciRemark::ReadyOk => return Ok(remarks),
+                rmk => remarks.push(rmk),
+            }
+        }
+    }
+
+    /// Stops any search left running by a check, failed or not, and waits
+    /// for the engine to be idle so that the next check starts afresh.
+    fn settle(&mut self) {
+        if self.searching && self.send(UciCommand::Stop).is_ok() {
+            while self.searching {
+                match self.recv_within(self.config.timeout) {
+                    Ok(Some(_)) => {}
+                    Ok(None) | Err(_) => break,
+                }
+            }
+        }
+        self.searching = false;
+        let _ = self.sync();
+    }
+
+    fn search(&mut self, moves: &[&str], params: UciGoParams) -> Result<(), String> {
+        let moves = moves.iter().map(|mv| mv.parse().unwrap()).collect();
+        self.send(UciCommand::Position {
+            init_pos: UciInitPos::StartPos,
+            moves,
+        })?;
+        self.send(UciCommand::Go(params))?;
+        self.searching = true;
+        Ok(())
+    }
+
+    fn search_infinite(&mut self) -> Result<(), String> {
+        self.search(
+            &[],
+            UciGoParams {
+                infinite: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn expect_no_bestmove(&mut self, duration: Duration, context: &str) -> Result<(), String> {
+        let deadline = Instant::now() + duration;
+        while let Some(rmk) =
+            self.recv_within(deadline.saturating_duration_since(Instant::now()))?
+        {
+            if let UciRemark::BestMove { .. } = rmk {
+                return Err(format!("engine sent bestmove {}", context));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_uci(&mut self) -> Result<(), String> {
+        self.send(UciCommand::Uci)?;
+        let mut has_name = false;
+        loop {
+            match self.recv("uciok")? {
+                UciRemark::UciOk => break,
+                UciRemark::Id(UciIdInfo::Name(_)) => has_name = true,
+                _ => {}
+            }
+        }
+        if !has_name {
+            return Err("engine did not send `id name` before uciok".to_owned());
+        }
+        for rmk in self.sync()? {
+            if let UciRemark::Id(_) | UciRemark::Option { .. } = rmk {
+                return Err(format!(
+                    "engine sent `{}` after uciok",
+                    rmk.format(&self.engine.options)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_isready(&mut self) -> Result<(), String> {
+        self.sync().map(drop)
+    }
+
+    fn check_go_infinite(&mut self) -> Result<(), String> {
+        self.send(UciCommand::UciNewGame)?;
+        self.sync()?;
+        self.search_infinite()?;
+        self.expect_no_bestmove(self.config.search_time, "during go infinite")
+    }
+
+    fn check_isready_during_search(&mut self) -> Result<(), String> {
+        self.search_infinite()?;
+        for rmk in self.sync()? {
+            if let UciRemark::BestMove { .. } = rmk {
+                return Err("engine sent bestmove instead of continuing the search".to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    fn check_stop(&mut self) -> Result<(), String> {
+        self.search_infinite()?;
+        self.send(UciCommand::Stop)?;
+        loop {
+            if let rmk @ UciRemark::BestMove { .. } = self.recv("bestmove")? {
+                UciMoveValidator::new(&UciInitPos::StartPos, &[], &self.engine.options)
+                    .and_then(|v| v.validate_remark(&rmk))
+                    .map_err(|e| e.to_string())?;
+                break;
+            }
+        }
+        for rmk in self.sync()? {
+            if let UciRemark::BestMove { .. } = rmk {
+                return Err("engine sent more than one bestmove after stop".to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    fn check_ponderhit(&mut self) -> Result<(), String> {
+        self.search(
+            &["e2e4", "e7e5"],
+            UciGoParams {
+                ponder: true,
+                wtime: Some(Duration::from_secs(2).into()),
+                btime: Some(Duration::from_secs(2).into()),
+                ..Default::default()
+            },
+        )?;
+        self.expect_no_bestmove(self.config.search_time, "while pondering")?;
+        self.send(UciCommand::PonderHit)?;
+        while !matches!(
+            self.recv("bestmove after ponderhit")?,
+            UciRemark::BestMove { .. }
+        ) {}
+        self.sync().map(drop)
+    }
+
+    fn check_new_game(&mut self) -> Result<(), String> {
+        self.send(UciCommand::UciNewGame)?;
+        self.sync()?;
+        let init_pos = UciInitPos::Board(NEW_GAME_FEN.parse().unwrap());
+        self.send(UciCommand::Position {
+            init_pos: init_pos.clone(),
+            moves: Vec::new(),
+        })?;
+        self.send(UciCommand::Go(UciGoParams {
+            movetime: Some(self.config.search_time),
+            ..Default::default()
+        }))?;
+        let validator = UciMoveValidator::new(&init_pos, &[], &self.engine.options)
+            .map_err(|e| e.to_string())?;
+        loop {
+            let rmk = self.recv("bestmove")?;
+            validator.validate_remark(&rmk).map_err(|e| e.to_string())?;
+            if let UciRemark::BestMove { .. } = rmk {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::prelude::*;
+use std::io::{self, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use thiserror::*;
+
+use crate::command::UciCommand;
+use crate::remark::{UciIdInfo, UciRemark};
+use crate::{UciFormatOptions, UciParseError};
+
+#[derive(Debug, Error)]
+pub enum UciEngineError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("timed out waiting for engine")]
+    Timeout,
+    #[error("engine disconnected")]
+    Disconnected,
+    #[error("failed to parse {line:?}: {error}")]
+    Parse { line: String, error: UciParseError },
+}
+
+/// A connection to a UCI engine, usually a spawned child process.
+///
+/// Lines from the engine are read on a background thread so that
+/// receiving can time out.
+pub struct UciEngine {
+    writer: Box<dyn Write + Send>,
+    lines: Receiver<io::Result<String>>,
+    child: Option<Child>,
+    pub options: UciFormatOptions,
+}
+
+impl UciEngine {
+    pub fn spawn(command: &mut Command) -> io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let mut engine = Self::from_io(BufReader::new(stdout), stdin);
+        engine.child = Some(child);
+        Ok(engine)
+    }
+
+    pub fn from_io(
+        reader: impl BufRead + Send + 'static,
+        writer: impl Write + Send + 'static,
+    ) -> Self {
+        let (sender, lines) = channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let error = line.is_err();
+                if sender.send(line).is_err() || error {
+                    break;
+                }
+            }
+        });
+        Self {
+            writer: Box::new(writer),
+            lines,
+            child: None,
+            options: UciFormatOptions::default(),
+        }
+    }
+
+    pub fn send_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()
+    }
+
+    pub fn send(&mut self, cmd: &UciCommand) -> io::Result<()> {
+        self.options.update(cmd);
+        let line = cmd.format(&self.options);
+        self.send_line(&line)
+    }
+
+    pub fn recv_line(&mut self, timeout: Duration) -> Result<String, UciEngineError> {
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => Ok(line?),
+            Err(RecvTimeoutError::Timeout) => Err(UciEngineError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Err(UciEngineError::Disconnected),
+        }
+    }
+
+    pub fn recv(&mut self, timeout: Duration) -> Result<UciRemark, UciEngineError> {
+        let line = self.recv_line(timeout)?;
+        let rmk = UciRemark::parse_from(&line, &self.options)
+            .map_err(|error| UciEngineError::Parse { line, error })?;
+        self.options.update_from_remark(&rmk);
+        Ok(rmk)
+    }
+
+    /// Sends `uci` and waits for `uciok`, returning the engine's `id name`.
+    /// Lines that fail to parse are skipped.
+    pub fn handshake(&mut self, timeout: Duration) -> Result<Option<String>, UciEngineError> {
+        self.send(&UciCommand::Uci)?;
+        let mut name = None;
+        loop {
+            match self.recv(timeout) {
+                Ok(UciRemark::Id(UciIdInfo::Name(n))) => name = Some(n),
+                Ok(UciRemark::UciOk) => return Ok(name),
+                Ok(_) | Err(UciEngineError::Parse { .. }) => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Sends `isready` and waits for `readyok`, skipping other lines.
+    pub fn wait_ready(&mut self, timeout: Duration) -> Result<(), UciEngineError> {
+        self.send(&UciCommand::IsReady)?;
+        loop {
+            match self.recv(timeout) {
+                Ok(UciRemark::ReadyOk) => return Ok(()),
+                Ok(_) | Err(UciEngineError::Parse { .. }) => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Sends `quit` and waits up to `timeout` for the engine to exit,
+    /// killing it if it does not.
+    pub fn quit(mut self, timeout: Duration) -> io::Result<()> {
+        let _ = self.send(&UciCommand::Quit);
+        if let Some(mut child) = self.child.take() {
+            let start = Instant::now();
+            while child.try_wait()?.is_none() {
+                if start.elapsed() > timeout {
+                    child.kill()?;
+                    child.wait()?;
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
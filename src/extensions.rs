@@ -0,0 +1,57 @@
+//! De facto extensions implemented by Stockfish and many other engines.
+
+use std::time::Duration;
+
+use cozy_chess::{Board, Square};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UciBenchLimitType {
+    Depth,
+    Perft,
+    Nodes,
+    Movetime,
+    Eval,
+}
+
+/// The positional arguments of `bench`. Arguments can only be omitted from
+/// the end, so a field may only be set if all fields before it are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct UciBenchParams {
+    pub hash: Option<u32>,
+    pub threads: Option<u32>,
+    pub limit: Option<u64>,
+    pub fen_file: Option<String>,
+    pub limit_type: Option<UciBenchLimitType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UciExtCommand {
+    /// `d`, which displays the current position.
+    D,
+    Eval,
+    Bench(UciBenchParams),
+    Flip,
+    Compiler,
+    ExportNet(Option<String>),
+}
+
+/// Lines printed in response to extension commands.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UciExtRemark {
+    /// `Fen:` from `d`.
+    Fen(Board),
+    /// `Key:` from `d`.
+    Key(u64),
+    /// `Checkers:` from `d`.
+    Checkers(Vec<Square>),
+    /// `Final evaluation` from `eval`, in centipawns from White's point of
+    /// view, with the explanation following it. The evaluation is `None` in
+    /// check.
+    FinalEvaluation { cp: Option<i32>, note: String },
+    /// `Total time (ms)` from `bench`.
+    TotalTime(Duration),
+    /// `Nodes searched` from `bench`.
+    NodesSearched(u64),
+    /// `Nodes/second` from `bench`.
+    NodesPerSecond(u64),
+}
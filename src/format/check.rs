@@ -0,0 +1,111 @@
+use cozy_chess::{Move, Piece};
+
+use super::error::{UciFormatError, UciFormatErrorKind};
+use crate::parse::remark::read_option_info;
+use crate::{UciTokenStream, UCI_NULL_MOVE};
+use UciFormatErrorKind::*;
+
+pub fn check_string(
+    field: &'static str,
+    s: &str,
+    reserved: &[&'static str],
+) -> Result<(), UciFormatError> {
+    if s.contains(['\n', '\r']) {
+        return Err(LineBreak.in_field(field));
+    }
+    if s.trim_matches(|c: char| c.is_ascii_whitespace()) != s {
+        return Err(SurroundingWhitespace.in_field(field));
+    }
+    for tok in s.split_ascii_whitespace() {
+        if let Some(&reserved) = reserved.iter().find(|&&r| r == tok) {
+            return Err(ReservedToken(reserved).in_field(field));
+        }
+    }
+    Ok(())
+}
+
+pub fn check_token(field: &'static str, s: &str) -> Result<(), UciFormatError> {
+    if s.is_empty() {
+        return Err(EmptyToken.in_field(field));
+    }
+    if s.contains(|c: char| c.is_ascii_whitespace()) {
+        return Err(WhitespaceInToken.in_field(field));
+    }
+    Ok(())
+}
+
+/// Checks a single move, where the null move cannot be written.
+pub fn check_move(field: &'static str, mv: Move) -> Result<(), UciFormatError> {
+    if let Some(Piece::Pawn | Piece::King) = mv.promotion {
+        return Err(InvalidPromotion(mv).in_field(field));
+    }
+    Ok(())
+}
+
+/// Checks a move list, where the null move is written as `0000`.
+pub fn check_moves(field: &'static str, moves: &[Move]) -> Result<(), UciFormatError> {
+    for &mv in moves {
+        if mv != UCI_NULL_MOVE {
+            check_move(field, mv)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a `setoption` name reads back as itself. The parser ends the
+/// name at the first `value` token, or at a later one if that makes it the
+/// name of a declared option.
+pub fn check_setoption_name(
+    name: &str,
+    value: Option<&str>,
+    declared: &[String],
+) -> Result<(), UciFormatError> {
+    let line = match value {
+        Some(value) => format!("{} value {}", name, value),
+        None => name.to_owned(),
+    };
+    let is_declared = |n: &str| declared.iter().any(|d| d == n);
+    let mut ends = Vec::new();
+    let mut s = UciTokenStream::new(&line);
+    let mut end = 0;
+    loop {
+        if let Ok(("value", _)) | Err(_) = s.peek_token() {
+            ends.push(end);
+        }
+        match s.read_token() {
+            Ok((_, span)) => end = span.end,
+            Err(_) => break,
+        }
+    }
+    let read = ends
+        .iter()
+        .map(|&end| &line[..end])
+        .find(|&n| is_declared(n))
+        .unwrap_or(&line[..ends[0]]);
+    match read {
+        read if read == name => Ok(()),
+        read if is_declared(read) => Err(DeclaredOptionName(read.to_owned()).in_field("name")),
+        _ => Err(ReservedToken("value").in_field("name")),
+    }
+}
+
+/// Checks that an `option` name reads back as itself, given the rest of the
+/// line after it. The parser ends the name at the first `type` token after
+/// which the rest of the line reads as the option's type.
+pub fn check_option_name(name: &str, rest: &str) -> Result<(), UciFormatError> {
+    let mut s = UciTokenStream::new(name);
+    while let Ok((tok, span)) = s.read_token() {
+        if tok != "type" {
+            continue;
+        }
+        let line = format!("{} {}", &name[span.start..], rest);
+        let mut s = UciTokenStream::new(&line);
+        if read_option_info(&mut s)
+            .and_then(|_| s.expect_end())
+            .is_ok()
+        {
+            return Err(ReservedToken("type").in_field("name"));
+        }
+    }
+    Ok(())
+}
@@ -3,23 +3,82 @@ use crate::UciFormatOptions;
 
 use std::fmt::{Display, Formatter};
 
+use cozy_chess::Board;
+
+use super::check::*;
+use super::error::{UciFormatError, UciFormatErrorKind};
+use super::{single_line, write_moves};
+
 struct UciCommandFormatter<'f> {
     command: &'f UciCommand,
     options: &'f UciFormatOptions,
 }
 
 impl UciCommand {
-    pub fn format<'f>(&'f self, options: &'f UciFormatOptions) -> String {
-        format!(
+    /// Formats the command.
+    ///
+    /// This is lossy for commands that cannot be represented: line breaks in
+    /// strings become spaces, and other fields are written as they are even
+    /// if the output would not parse back to the same command. Use
+    /// [`UciCommand::try_format`] to check for these cases instead.
+    pub fn format(&self, options: &UciFormatOptions) -> String {
+        single_line(format!(
+            "{}",
+            UciCommandFormatter {
+                command: self,
+                options
+            }
+        ))
+    }
+
+    /// Formats the command, failing if the output would not parse back to the same command.
+    pub fn try_format(&self, options: &UciFormatOptions) -> Result<String, UciFormatError> {
+        check_command(self, options)?;
+        Ok(format!(
             "{}",
             UciCommandFormatter {
                 command: self,
                 options
             }
-        )
+        ))
     }
 }
 
+fn check_command(command: &UciCommand, options: &UciFormatOptions) -> Result<(), UciFormatError> {
+    use UciCommand::*;
+
+    match command {
+        Position { init_pos, moves } => {
+            if let UciInitPos::Board(board) = init_pos {
+                let fen = match options.chess960 {
+                    false => format!("{}", board),
+                    true => format!("{:#}", board),
+                };
+                if Board::from_fen(&fen, options.chess960).ok().as_ref() != Some(board) {
+                    return Err(UciFormatErrorKind::UnrepresentableBoard.in_field("fen"));
+                }
+            }
+            check_moves("moves", moves)?;
+        }
+        SetOption { name, value } => {
+            check_string("name", name, &[])?;
+            if let Some(value) = value {
+                check_string("value", value, &[])?;
+            }
+            check_setoption_name(name, value.as_deref(), &options.option_names)?;
+        }
+        Ext(command) => super::extensions::check_ext_command(command)?,
+        Custom(command) => command.check()?,
+        Go(params) => {
+            if let Some(searchmoves) = &params.searchmoves {
+                check_moves("searchmoves", searchmoves)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 impl Display for UciCommandFormatter<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         use UciCommand::*;
@@ -39,9 +98,7 @@ impl Display for UciCommandFormatter<'_> {
                 }
                 if !moves.is_empty() {
                     write!(f, " moves")?;
-                    for mv in moves {
-                        write!(f, " {}", mv)?;
-                    }
+                    write_moves(f, moves)?;
                 }
             }
             SetOption { name, value } => {
@@ -55,6 +112,11 @@ impl Display for UciCommandFormatter<'_> {
             PonderHit => write!(f, "ponderhit")?,
             Quit => write!(f, "quit")?,
             Go(params) => format_go_params(f, params)?,
+            Custom(command) => {
+                write!(f, "{}", command.dyn_kind())?;
+                command.format_args(f)?;
+            }
+            Ext(command) => super::extensions::write_ext_command(f, command)?,
         }
         Ok(())
     }
@@ -91,21 +153,20 @@ fn format_go_params(f: &mut Formatter, params: &UciGoParams) -> std::fmt::Result
     format_go_params! {
         searchmoves -> {
             write!(f, " searchmoves")?;
-            for mv in searchmoves {
-                write!(f, " {}", mv)?;
-            }
+            write_moves(f, searchmoves)?;
         },
         ponder => if *ponder { write!(f, " ponder")? },
-        wtime -> write!(f, " wtime {}", wtime.as_millis())?,
-        btime -> write!(f, " btime {}", btime.as_millis())?,
-        winc -> write!(f, " winc {}", winc.as_millis())?,
-        binc -> write!(f, " binc {}", binc.as_millis())?,
+        wtime,
+        btime,
+        winc,
+        binc,
         movestogo,
         depth,
         nodes,
         mate,
         movetime -> write!(f, " movetime {}", movetime.as_millis())?,
         infinite => if *infinite { write!(f, " infinite")? },
+        perft,
     }
     Ok(())
 }
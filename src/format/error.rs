@@ -0,0 +1,41 @@
+use cozy_chess::Move;
+use thiserror::*;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("cannot format {field}: {kind}")]
+pub struct UciFormatError {
+    pub field: &'static str,
+    pub kind: UciFormatErrorKind,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum UciFormatErrorKind {
+    #[error("string contains a line break")]
+    LineBreak,
+    #[error("string has leading or trailing whitespace")]
+    SurroundingWhitespace,
+    #[error("string contains reserved token `{0}`")]
+    ReservedToken(&'static str),
+    #[error("string would be read as the declared option `{0}`")]
+    DeclaredOptionName(String),
+    #[error("token is empty")]
+    EmptyToken,
+    #[error("token contains whitespace")]
+    WhitespaceInToken,
+    #[error("key contains `=`")]
+    EqualsInKey,
+    #[error("move {0} has an invalid promotion")]
+    InvalidPromotion(Move),
+    #[error("board cannot be represented in this fen format")]
+    UnrepresentableBoard,
+    #[error("wdl is disabled")]
+    WdlDisabled,
+    #[error("argument is set but the preceding `{0}` is not")]
+    SkippedArgument(&'static str),
+}
+
+impl UciFormatErrorKind {
+    pub fn in_field(self, field: &'static str) -> UciFormatError {
+        UciFormatError { field, kind: self }
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt::{Formatter, Result};
+
+use cozy_chess::Board;
+
+use crate::extensions::*;
+use crate::UciFormatOptions;
+
+use super::check::*;
+use super::error::{UciFormatError, UciFormatErrorKind};
+use UciFormatErrorKind::*;
+
+pub(super) fn check_ext_command(
+    command: &UciExtCommand,
+) -> std::result::Result<(), UciFormatError> {
+    match command {
+        UciExtCommand::Bench(params) => {
+            let set = [
+                ("hash", params.hash.is_some()),
+                ("threads", params.threads.is_some()),
+                ("limit", params.limit.is_some()),
+                ("fen_file", params.fen_file.is_some()),
+                ("limit_type", params.limit_type.is_some()),
+            ];
+            for pair in set.windows(2) {
+                if !pair[0].1 && pair[1].1 {
+                    return Err(SkippedArgument(pair[0].0).in_field(pair[1].0));
+                }
+            }
+            if let Some(fen_file) = &params.fen_file {
+                check_token("fen_file", fen_file)?;
+            }
+        }
+        UciExtCommand::ExportNet(Some(file)) => {
+            if file.is_empty() {
+                return Err(EmptyToken.in_field("file"));
+            }
+            check_string("file", file, &[])?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+pub(super) fn check_ext_remark(
+    remark: &UciExtRemark,
+    options: &UciFormatOptions,
+) -> std::result::Result<(), UciFormatError> {
+    match remark {
+        UciExtRemark::Fen(board) => {
+            let fen = format_fen(board, options);
+            if Board::from_fen(&fen, options.chess960).ok().as_ref() != Some(board) {
+                return Err(UnrepresentableBoard.in_field("fen"));
+            }
+        }
+        UciExtRemark::FinalEvaluation { note, .. } => check_string("note", note, &[])?,
+        _ => {}
+    }
+    Ok(())
+}
+
+pub(super) fn write_ext_command(f: &mut Formatter, command: &UciExtCommand) -> Result {
+    match command {
+        UciExtCommand::D => write!(f, "d"),
+        UciExtCommand::Eval => write!(f, "eval"),
+        UciExtCommand::Bench(params) => {
+            write!(f, "bench")?;
+            let UciBenchParams {
+                hash,
+                threads,
+                limit,
+                fen_file,
+                limit_type,
+            } = params;
+            let args = [
+                hash.map(|v| v.to_string()),
+                threads.map(|v| v.to_string()),
+                limit.map(|v| v.to_string()),
+                fen_file.clone(),
+                limit_type.map(|t| bench_limit_type_str(t).to_owned()),
+            ];
+            for arg in args.iter().map_while(Option::as_ref) {
+                write!(f, " {}", arg)?;
+            }
+            Ok(())
+        }
+        UciExtCommand::Flip => write!(f, "flip"),
+        UciExtCommand::Compiler => write!(f, "compiler"),
+        UciExtCommand::ExportNet(file) => {
+            write!(f, "export_net")?;
+            match file {
+                Some(file) => write!(f, " {}", file),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+pub(super) fn write_ext_remark(
+    f: &mut Formatter,
+    remark: &UciExtRemark,
+    options: &UciFormatOptions,
+) -> Result {
+    match remark {
+        UciExtRemark::Fen(board) => write!(f, "Fen: {}", format_fen(board, options)),
+        UciExtRemark::Key(key) => write!(f, "Key: {:016X}", key),
+        UciExtRemark::Checkers(checkers) => {
+            write!(f, "Checkers:")?;
+            for square in checkers {
+                write!(f, " {}", square)?;
+            }
+            Ok(())
+        }
+        UciExtRemark::FinalEvaluation { cp, note } => {
+            write!(f, "Final evaluation       ")?;
+            match cp {
+                Some(cp) => {
+                    let sign = if *cp < 0 { '-' } else { '+' };
+                    let abs = cp.unsigned_abs();
+                    write!(f, "{}{}.{:02}", sign, abs / 100, abs % 100)?;
+                }
+                None => write!(f, "none")?,
+            }
+            if !note.is_empty() {
+                write!(f, " {}", note)?;
+            }
+            Ok(())
+        }
+        UciExtRemark::TotalTime(time) => write!(f, "Total time (ms) : {}", time.as_millis()),
+        UciExtRemark::NodesSearched(nodes) => write!(f, "Nodes searched  : {}", nodes),
+        UciExtRemark::NodesPerSecond(nps) => write!(f, "Nodes/second    : {}", nps),
+    }
+}
+
+fn format_fen(board: &Board, options: &UciFormatOptions) -> String {
+    match options.chess960 {
+        false => format!("{}", board),
+        true => format!("{:#}", board),
+    }
+}
+
+fn bench_limit_type_str(limit_type: UciBenchLimitType) -> &'static str {
+    match limit_type {
+        UciBenchLimitType::Depth => "depth",
+        UciBenchLimitType::Perft => "perft",
+        UciBenchLimitType::Nodes => "nodes",
+        UciBenchLimitType::Movetime => "movetime",
+        UciBenchLimitType::Eval => "eval",
+    }
+}
@@ -0,0 +1,25 @@
+use crate::message::UciMessage;
+use crate::UciFormatOptions;
+
+use super::error::UciFormatError;
+
+impl UciMessage {
+    /// Formats the message. This is lossy in the same way as
+    /// [`UciCommand::format`](crate::command::UciCommand::format) and
+    /// [`UciRemark::format`](crate::remark::UciRemark::format); use
+    /// [`UciMessage::try_format`] to check for unrepresentable messages.
+    pub fn format(&self, options: &UciFormatOptions) -> String {
+        match self {
+            Self::Command(cmd) => cmd.format(options),
+            Self::Remark(rmk) => rmk.format(options),
+        }
+    }
+
+    /// Formats the message, failing if the output would not parse back to the same message.
+    pub fn try_format(&self, options: &UciFormatOptions) -> Result<String, UciFormatError> {
+        match self {
+            Self::Command(cmd) => cmd.try_format(options),
+            Self::Remark(rmk) => rmk.try_format(options),
+        }
+    }
+}
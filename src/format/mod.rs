@@ -1,2 +1,33 @@
+mod check;
 mod command;
+mod error;
+mod extensions;
+mod message;
 mod remark;
+
+pub use error::{UciFormatError, UciFormatErrorKind};
+
+use std::fmt::{Formatter, Result};
+
+use cozy_chess::Move;
+
+use crate::UCI_NULL_MOVE;
+
+/// Keeps a formatted message on one line, as it would otherwise be read
+/// back as several messages.
+fn single_line(line: String) -> String {
+    match line.contains(['\n', '\r']) {
+        true => line.replace(['\n', '\r'], " "),
+        false => line,
+    }
+}
+
+fn write_moves(f: &mut Formatter, moves: &[Move]) -> Result {
+    for &mv in moves {
+        match mv {
+            UCI_NULL_MOVE => write!(f, " 0000")?,
+            mv => write!(f, " {}", mv)?,
+        }
+    }
+    Ok(())
+}
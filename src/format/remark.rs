@@ -3,23 +3,110 @@ use crate::UciFormatOptions;
 
 use std::fmt::{Display, Formatter};
 
+use super::check::*;
+use super::error::{UciFormatError, UciFormatErrorKind};
+use super::{single_line, write_moves};
+
 struct UciRemarkFormatter<'f> {
     remark: &'f UciRemark,
     options: &'f UciFormatOptions,
 }
 
 impl UciRemark {
-    pub fn format<'f>(&'f self, options: &'f UciFormatOptions) -> String {
-        format!(
+    /// Formats the remark.
+    ///
+    /// This is lossy for remarks that cannot be represented: `wdl` is dropped
+    /// unless [`UciFormatOptions::wdl`] is set, line breaks in strings become
+    /// spaces, and other fields are written as they are even if the output
+    /// would not parse back to the same remark. Use [`UciRemark::try_format`]
+    /// to check for these cases instead.
+    pub fn format(&self, options: &UciFormatOptions) -> String {
+        single_line(format!(
             "{}",
             UciRemarkFormatter {
                 remark: self,
                 options
             }
-        )
+        ))
+    }
+
+    /// Formats the remark, failing if the output would not parse back to the same remark.
+    pub fn try_format(&self, options: &UciFormatOptions) -> Result<String, UciFormatError> {
+        check_remark(self, options)?;
+        Ok(format!(
+            "{}",
+            UciRemarkFormatter {
+                remark: self,
+                options
+            }
+        ))
     }
 }
 
+fn check_remark(remark: &UciRemark, options: &UciFormatOptions) -> Result<(), UciFormatError> {
+    use UciIdInfo::*;
+    use UciOptionInfo::*;
+    use UciRemark::*;
+
+    match remark {
+        Id(Name(name)) => check_string("name", name, &[])?,
+        Id(Author(author)) => check_string("author", author, &[])?,
+        BestMove { mv, ponder } => {
+            if let UciBestMove::Move(mv) = mv {
+                check_move("bestmove", *mv)?;
+            }
+            if let Some(ponder) = ponder {
+                check_move("ponder", *ponder)?;
+            }
+        }
+        Info(info) => check_info(info, options)?,
+        Option { name, info } => {
+            check_string("name", name, &[])?;
+            match info {
+                Combo { default, labels } => {
+                    check_string("default", default, &["var"])?;
+                    for label in labels {
+                        check_string("var", label, &["var"])?;
+                    }
+                }
+                String { default } => check_string("default", default, &[])?,
+                _ => {}
+            }
+            let line = UciRemarkFormatter { remark, options }.to_string();
+            let rest = &line["option name ".len() + name.len()..];
+            check_option_name(name, rest.trim_start())?;
+        }
+        Custom(remark) => remark.check()?,
+        Ext(remark) => super::extensions::check_ext_remark(remark, options)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn check_info(info: &UciInfo, options: &UciFormatOptions) -> Result<(), UciFormatError> {
+    if let Some(pv) = &info.pv {
+        check_moves("pv", pv)?;
+    }
+    if let Some(score) = &info.score {
+        if score.wdl.is_some() && !options.wdl {
+            return Err(UciFormatErrorKind::WdlDisabled.in_field("score"));
+        }
+    }
+    if let Some(currmove) = info.currmove {
+        check_move("currmove", currmove)?;
+    }
+    if let Some(string) = &info.string {
+        check_string("string", string, &[])?;
+    }
+    if let Some(refutation) = &info.refutation {
+        check_moves("refutation", refutation)?;
+    }
+    if let Some(currline) = &info.currline {
+        check_moves("currline", &currline.moves)?;
+    }
+    Ok(())
+}
+
 impl Display for UciRemarkFormatter<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         use UciIdInfo::*;
@@ -32,7 +119,11 @@ impl Display for UciRemarkFormatter<'_> {
             UciOk => write!(f, "uciok")?,
             ReadyOk => write!(f, "readyok")?,
             BestMove { mv, ponder } => {
-                write!(f, "bestmove {}", mv)?;
+                match mv {
+                    UciBestMove::Move(mv) => write!(f, "bestmove {}", mv)?,
+                    UciBestMove::None => write!(f, "bestmove (none)")?,
+                    UciBestMove::Null => write!(f, "bestmove 0000")?,
+                }
                 if let Some(mv) = ponder {
                     write!(f, " ponder {}", mv)?;
                 }
@@ -55,6 +146,11 @@ impl Display for UciRemarkFormatter<'_> {
                     String { default } => write!(f, "string default {}", default)?,
                 }
             }
+            Custom(remark) => {
+                write!(f, "{}", remark.dyn_kind())?;
+                remark.format_args(f)?;
+            }
+            Ext(remark) => super::extensions::write_ext_remark(f, remark, self.options)?,
         }
         Ok(())
     }
@@ -88,9 +184,7 @@ impl UciRemarkFormatter<'_> {
             nodes,
             pv -> {
                 write!(f, " pv")?;
-                for mv in pv {
-                    write!(f, " {}", mv)?;
-                }
+                write_moves(f, pv)?;
             },
             multipv,
             score -> {
@@ -119,22 +213,19 @@ impl UciRemarkFormatter<'_> {
             tbhits,
             sbhits,
             cpuload,
-            string,
             refutation -> {
                 write!(f, " refutation")?;
-                for mv in refutation {
-                    write!(f, " {}", mv)?;
-                }
+                write_moves(f, refutation)?;
             },
             currline -> {
                 write!(f, " currline")?;
                 if let Some(cpu) = currline.cpu {
                     write!(f, " {}", cpu)?;
                 }
-                for mv in &currline.moves {
-                    write!(f, " {}", mv)?;
-                }
+                write_moves(f, &currline.moves)?;
             },
+            // `string` consumes the rest of the line, so it must come last.
+            string,
         }
         Ok(())
     }
@@ -1,5 +1,31 @@
+use crate::command::UciCommand;
+use crate::remark::UciRemark;
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct UciFormatOptions {
     pub chess960: bool,
     pub wdl: bool,
+    /// Names of the options declared by the engine. A `setoption` name
+    /// containing `value` only parses if it is listed here.
+    pub option_names: Vec<String>,
+}
+
+impl UciFormatOptions {
+    pub fn update(&mut self, cmd: &UciCommand) {
+        if let UciCommand::SetOption { name, value } = cmd {
+            match name.as_str() {
+                "UCI_Chess960" => self.chess960 = value.as_deref() == Some("true"),
+                "UCI_ShowWDL" => self.wdl = value.as_deref() == Some("true"),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update_from_remark(&mut self, rmk: &UciRemark) {
+        if let UciRemark::Option { name, .. } = rmk {
+            if !self.option_names.contains(name) {
+                self.option_names.push(name.clone());
+            }
+        }
+    }
 }
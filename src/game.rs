@@ -0,0 +1,463 @@
+//! Playing games between two engines and writing them as PGN.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use cozy_chess::util::display_san_move;
+use cozy_chess::{BitBoard, Board, Color, Move, Piece};
+use thiserror::*;
+
+use crate::command::{UciCommand, UciGoParams, UciInitPos};
+use crate::engine::{UciEngine, UciEngineError};
+use crate::moves::{from_uci_move, play_uci_move};
+use crate::remark::{UciBestMove, UciRemark, UciScoreValue};
+
+/// A time control of `BASE+INC` in seconds, such as `60+0.5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UciTimeControl {
+    pub base: Duration,
+    pub inc: Duration,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid time control {0:?}")]
+pub struct UciTimeControlError(pub String);
+
+impl FromStr for UciTimeControl {
+    type Err = UciTimeControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || UciTimeControlError(s.to_owned());
+        let (base, inc) = s.split_once('+').unwrap_or((s, "0"));
+        let secs = |s: &str| {
+            s.parse::<f64>()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        };
+        Ok(Self {
+            base: secs(base).ok_or_else(error)?,
+            inc: secs(inc).ok_or_else(error)?,
+        })
+    }
+}
+
+impl Display for UciTimeControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}+{}", self.base.as_secs_f64(), self.inc.as_secs_f64())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UciGameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl UciGameResult {
+    fn win(color: Color) -> Self {
+        match color {
+            Color::White => Self::WhiteWins,
+            Color::Black => Self::BlackWins,
+        }
+    }
+}
+
+impl Display for UciGameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WhiteWins => write!(f, "1-0"),
+            Self::BlackWins => write!(f, "0-1"),
+            Self::Draw => write!(f, "1/2-1/2"),
+        }
+    }
+}
+
+/// How a game ended.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UciTermination {
+    Checkmate,
+    Stalemate,
+    FiftyMoves,
+    Repetition,
+    InsufficientMaterial,
+    /// The side to move ran out of time. The game is a draw if the other
+    /// side has insufficient material to mate.
+    TimeForfeit,
+    /// The side to move played an illegal move, written as sent.
+    IllegalMove(String),
+    /// The side to move stopped responding or failed to search.
+    Disconnected(String),
+}
+
+impl UciTermination {
+    /// The value of the PGN `Termination` tag.
+    pub fn pgn_tag(&self) -> &'static str {
+        match self {
+            Self::TimeForfeit => "time forfeit",
+            Self::IllegalMove(_) => "rules infraction",
+            Self::Disconnected(_) => "abandoned",
+            _ => "normal",
+        }
+    }
+}
+
+/// A move played in a game along with the engine's last reported search.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UciGameMove {
+    pub mv: Move,
+    pub score: Option<UciScoreValue>,
+    pub depth: Option<u32>,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UciGameSettings {
+    pub init_pos: UciInitPos,
+    pub time_control: UciTimeControl,
+    /// How far an engine may overrun its clock before losing on time.
+    pub margin: Duration,
+    pub chess960: bool,
+}
+
+impl Default for UciGameSettings {
+    fn default() -> Self {
+        Self {
+            init_pos: UciInitPos::StartPos,
+            time_control: UciTimeControl {
+                base: Duration::from_secs(10),
+                inc: Duration::from_millis(100),
+            },
+            margin: Duration::from_millis(50),
+            chess960: false,
+        }
+    }
+}
+
+/// A finished game. The side to move when the game ended is the one that
+/// lost on time, moved illegally or disconnected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UciGame {
+    pub settings: UciGameSettings,
+    pub moves: Vec<UciGameMove>,
+    pub result: UciGameResult,
+    pub termination: UciTermination,
+}
+
+impl UciGame {
+    /// The reason for the result in words, as written in the final comment
+    /// of the PGN.
+    pub fn reason(&self) -> String {
+        let start = Board::from(self.settings.init_pos.clone()).side_to_move();
+        let to_move = match self.moves.len() % 2 {
+            0 => start,
+            _ => !start,
+        };
+        let (mover, other) = match to_move {
+            Color::White => ("White", "Black"),
+            Color::Black => ("Black", "White"),
+        };
+        match &self.termination {
+            UciTermination::Checkmate => format!("{} mates", other),
+            UciTermination::Stalemate => "Draw by stalemate".to_owned(),
+            UciTermination::FiftyMoves => "Draw by fifty moves rule".to_owned(),
+            UciTermination::Repetition => "Draw by 3-fold repetition".to_owned(),
+            UciTermination::InsufficientMaterial => {
+                "Draw by insufficient mating material".to_owned()
+            }
+            UciTermination::TimeForfeit => match self.result {
+                UciGameResult::Draw => "Draw by timeout vs insufficient mating material".to_owned(),
+                _ => format!("{} loses on time", mover),
+            },
+            UciTermination::IllegalMove(mv) => format!("{} makes an illegal move: {}", mover, mv),
+            UciTermination::Disconnected(reason) => format!("{} disconnects: {}", mover, reason),
+        }
+    }
+
+    /// Writes the game as PGN. `tags` fill in the seven tag roster, except
+    /// `Result`, with `?` for any that are missing, and are followed by any
+    /// other tags given.
+    pub fn to_pgn(&self, tags: &[(&str, &str)]) -> String {
+        const ROSTER: [&str; 6] = ["Event", "Site", "Date", "Round", "White", "Black"];
+        let mut pgn = String::new();
+        let mut tag = |name: &str, value: &str| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            pgn += &format!("[{} \"{}\"]\n", name, value);
+        };
+        for name in ROSTER {
+            let value = tags.iter().find(|(n, _)| *n == name).map_or("?", |t| t.1);
+            tag(name, value);
+        }
+        tag("Result", &self.result.to_string());
+        for &(name, value) in tags {
+            if !ROSTER.contains(&name) {
+                tag(name, value);
+            }
+        }
+        let settings = &self.settings;
+        if let UciInitPos::Board(board) = &settings.init_pos {
+            tag("SetUp", "1");
+            match settings.chess960 {
+                false => tag("FEN", &board.to_string()),
+                true => tag("FEN", &format!("{:#}", board)),
+            }
+        }
+        if settings.chess960 {
+            tag("Variant", "Chess960");
+        }
+        tag("TimeControl", &settings.time_control.to_string());
+        tag("PlyCount", &self.moves.len().to_string());
+        tag("Termination", self.termination.pgn_tag());
+        pgn.push('\n');
+
+        let mut board: Board = settings.init_pos.clone().into();
+        let mut tokens = Vec::new();
+        for (i, game_move) in self.moves.iter().enumerate() {
+            match board.side_to_move() {
+                Color::White => tokens.push(format!("{}.", board.fullmove_number())),
+                Color::Black if i == 0 => tokens.push(format!("{}...", board.fullmove_number())),
+                Color::Black => {}
+            }
+            let mv = from_uci_move(&board, game_move.mv, settings.chess960);
+            if !board.is_legal(mv) {
+                break;
+            }
+            tokens.push(display_san_move(&board, mv).to_string());
+            tokens.push(format!("{{{}}}", move_comment(game_move)));
+            board.play_unchecked(mv);
+        }
+        tokens.push(format!("{{{}}}", self.reason()));
+        tokens.push(self.result.to_string());
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + token.len() >= 80 {
+                pgn += &line;
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &token;
+        }
+        pgn += &line;
+        pgn.push_str("\n\n");
+        pgn
+    }
+}
+
+/// Formats the UTC date of `time` as a PGN `Date` tag value.
+pub fn pgn_date(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |t| t.as_secs() / 86400) as i64;
+    // Converts days since 1970-01-01 to a civil date, from
+    // https://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+fn move_comment(game_move: &UciGameMove) -> String {
+    let time = format!("{:.3}s", game_move.time.as_secs_f64());
+    let score = match game_move.score {
+        Some(UciScoreValue::Cp(cp)) => format!("{:+.2}", cp as f64 / 100.0),
+        Some(UciScoreValue::Mate(moves)) if moves > 0 => format!("+M{}", moves),
+        Some(UciScoreValue::Mate(moves)) => format!("-M{}", -moves),
+        None => return time,
+    };
+    match game_move.depth {
+        Some(depth) => format!("{}/{} {}", score, depth, time),
+        None => format!("{} {}", score, time),
+    }
+}
+
+/// Whether neither side can possibly mate: only kings and at most one minor
+/// piece, or only kings and bishops all on squares of one colour.
+pub fn is_insufficient_material(board: &Board) -> bool {
+    insufficient_material(board, board.occupied())
+}
+
+/// Whether `color` alone could not mate: only its king and at most one
+/// minor piece, or only its king and bishops all on squares of one colour.
+pub fn has_insufficient_material(board: &Board, color: Color) -> bool {
+    insufficient_material(board, board.colors(color))
+}
+
+fn insufficient_material(board: &Board, pieces: BitBoard) -> bool {
+    let kings = board.pieces(Piece::King);
+    let others = pieces & !kings;
+    let minors = board.pieces(Piece::Knight) | board.pieces(Piece::Bishop);
+    if others.len() <= 1 && others == (others & minors) {
+        return true;
+    }
+    let bishops = pieces & board.pieces(Piece::Bishop);
+    others == bishops
+        && ((bishops & BitBoard::DARK_SQUARES).is_empty()
+            || (bishops & BitBoard::LIGHT_SQUARES).is_empty())
+}
+
+/// Plays a game between two engines, which must have completed their
+/// handshake with any options set. Time forfeits, illegal moves and
+/// engines that disconnect or stop responding end the game rather than
+/// returning an error.
+pub fn play_game(
+    white: &mut UciEngine,
+    black: &mut UciEngine,
+    settings: &UciGameSettings,
+) -> UciGame {
+    let mut game = UciGame {
+        settings: settings.clone(),
+        moves: Vec::new(),
+        result: UciGameResult::Draw,
+        termination: UciTermination::Stalemate,
+    };
+    let mut board: Board = settings.init_pos.clone().into();
+    let mut history = vec![board.hash()];
+    let tc = settings.time_control;
+    let mut clocks = [tc.base, tc.base];
+
+    for (color, engine) in [(Color::White, &mut *white), (Color::Black, &mut *black)] {
+        let ready = engine
+            .send(&UciCommand::UciNewGame)
+            .map_err(UciEngineError::from)
+            .and_then(|_| engine.wait_ready(Duration::from_secs(10)));
+        if let Err(err) = ready {
+            game.result = UciGameResult::win(!color);
+            game.termination = UciTermination::Disconnected(err.to_string());
+            return game;
+        }
+    }
+
+    loop {
+        let to_move = board.side_to_move();
+        if let Some((result, termination)) = game_over(&board, &history) {
+            game.result = result;
+            game.termination = termination;
+            return game;
+        }
+        let engine = match to_move {
+            Color::White => &mut *white,
+            Color::Black => &mut *black,
+        };
+        let position = UciCommand::Position {
+            init_pos: settings.init_pos.clone(),
+            moves: game.moves.iter().map(|m| m.mv).collect(),
+        };
+        let go = UciCommand::Go(UciGoParams {
+            wtime: Some(clocks[Color::White as usize].into()),
+            btime: Some(clocks[Color::Black as usize].into()),
+            winc: Some(tc.inc.into()),
+            binc: Some(tc.inc.into()),
+            ..Default::default()
+        });
+        let clock = clocks[to_move as usize];
+        let start = Instant::now();
+        let sent = engine.send(&position).and_then(|_| engine.send(&go));
+        let outcome = match sent {
+            Ok(()) => search(engine, clock + settings.margin, start),
+            Err(err) => Err(UciTermination::Disconnected(err.to_string())),
+        };
+        let opponent_cannot_mate = has_insufficient_material(&board, !to_move);
+        let end = |termination| match termination {
+            UciTermination::TimeForfeit if opponent_cannot_mate => {
+                (UciGameResult::Draw, termination)
+            }
+            _ => (UciGameResult::win(!to_move), termination),
+        };
+        let game_move = match outcome {
+            Ok(game_move) => game_move,
+            Err(termination) => {
+                (game.result, game.termination) = end(termination);
+                return game;
+            }
+        };
+        if game_move.time > clock + settings.margin {
+            (game.result, game.termination) = end(UciTermination::TimeForfeit);
+            return game;
+        }
+        if play_uci_move(&mut board, game_move.mv, settings.chess960).is_err() {
+            let illegal = UciTermination::IllegalMove(game_move.mv.to_string());
+            (game.result, game.termination) = end(illegal);
+            return game;
+        }
+        clocks[to_move as usize] = clock.saturating_sub(game_move.time) + tc.inc;
+        history.push(board.hash());
+        game.moves.push(game_move);
+    }
+}
+
+/// Waits for a `bestmove`, keeping the score and depth of the last `info`.
+fn search(
+    engine: &mut UciEngine,
+    limit: Duration,
+    start: Instant,
+) -> Result<UciGameMove, UciTermination> {
+    let (mut score, mut depth) = (None, None);
+    loop {
+        let remaining = limit.saturating_sub(start.elapsed());
+        match engine.recv(remaining) {
+            Ok(UciRemark::Info(info)) => {
+                if let Some(value) = info.score.and_then(|s| s.value()) {
+                    score = Some(value);
+                    depth = info.depth.or(depth);
+                }
+            }
+            Ok(UciRemark::BestMove { mv, .. }) => {
+                return match mv {
+                    UciBestMove::Move(mv) => Ok(UciGameMove {
+                        mv,
+                        score,
+                        depth,
+                        time: start.elapsed(),
+                    }),
+                    UciBestMove::None => Err(UciTermination::IllegalMove("(none)".to_owned())),
+                    UciBestMove::Null => Err(UciTermination::IllegalMove("0000".to_owned())),
+                }
+            }
+            Ok(_) | Err(UciEngineError::Parse { .. }) => {}
+            Err(UciEngineError::Timeout) => {
+                let _ = engine.send(&UciCommand::Stop);
+                return Err(UciTermination::TimeForfeit);
+            }
+            Err(err) => return Err(UciTermination::Disconnected(err.to_string())),
+        }
+    }
+}
+
+fn game_over(board: &Board, history: &[u64]) -> Option<(UciGameResult, UciTermination)> {
+    let mut has_moves = false;
+    board.generate_moves(|_| {
+        has_moves = true;
+        true
+    });
+    if !has_moves {
+        return Some(match board.checkers().is_empty() {
+            true => (UciGameResult::Draw, UciTermination::Stalemate),
+            false => (
+                UciGameResult::win(!board.side_to_move()),
+                UciTermination::Checkmate,
+            ),
+        });
+    }
+    let draw = |termination| Some((UciGameResult::Draw, termination));
+    if board.halfmove_clock() >= 100 {
+        return draw(UciTermination::FiftyMoves);
+    }
+    let hash = board.hash();
+    if history.iter().filter(|&&h| h == hash).count() >= 3 {
+        return draw(UciTermination::Repetition);
+    }
+    if is_insufficient_material(board) {
+        return draw(UciTermination::InsufficientMaterial);
+    }
+    None
+}
@@ -1,12 +1,45 @@
 //TODO consider parsing fields in any order
 
+pub mod adapter;
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+pub mod cecp;
 pub mod command;
+pub mod conform;
+pub mod engine;
+pub mod extensions;
 mod format;
 mod format_options;
+pub mod game;
+pub mod message;
+mod moves;
 mod parse;
+pub mod perft;
 mod permill;
+pub mod pov;
+pub mod progress;
+pub mod proxy;
+pub mod registry;
 pub mod remark;
+pub mod san;
+pub mod session;
+pub mod tournament;
+pub mod uci2xboard;
+pub mod validate;
+pub mod wdl;
+pub mod xboard2uci;
 
 pub use format::*;
 pub use format_options::*;
 pub use parse::*;
+
+use cozy_chess::{Move, Piece, Square};
+
+/// The null move, written as `0000` in move lists. It is a king promotion,
+/// which no other move string parses to, so a move such as `a1a1` is never
+/// mistaken for it.
+pub const UCI_NULL_MOVE: Move = Move {
+    from: Square::A1,
+    to: Square::A1,
+    promotion: Some(Piece::King),
+};
@@ -0,0 +1,21 @@
+use crate::command::UciCommand;
+use crate::remark::UciRemark;
+
+/// A message in either direction, for tools that handle both.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UciMessage {
+    Command(UciCommand),
+    Remark(UciRemark),
+}
+
+impl From<UciCommand> for UciMessage {
+    fn from(value: UciCommand) -> Self {
+        Self::Command(value)
+    }
+}
+
+impl From<UciRemark> for UciMessage {
+    fn from(value: UciRemark) -> Self {
+        Self::Remark(value)
+    }
+}
@@ -0,0 +1,54 @@
+use cozy_chess::{Board, File, Move, Rank, Square};
+
+use crate::UCI_NULL_MOVE;
+
+pub(crate) fn from_uci_move(board: &Board, mut mv: Move, chess960: bool) -> Move {
+    if chess960 {
+        return mv;
+    }
+    let color = board.side_to_move();
+    let first_rank = Rank::First.relative_to(color);
+    let rights = board.castle_rights(color);
+    if board.king(color) == mv.from && mv.from == Square::new(File::E, first_rank) {
+        if mv.to == Square::new(File::G, first_rank) {
+            if let Some(file) = rights.short {
+                mv.to = Square::new(file, first_rank);
+            }
+        }
+        if mv.to == Square::new(File::C, first_rank) {
+            if let Some(file) = rights.long {
+                mv.to = Square::new(file, first_rank);
+            }
+        }
+    }
+    mv
+}
+
+/// Plays a move as written in UCI, including the null move.
+pub(crate) fn play_uci_move(board: &mut Board, mv: Move, chess960: bool) -> Result<(), ()> {
+    if mv == UCI_NULL_MOVE {
+        *board = board.null_move().ok_or(())?;
+        return Ok(());
+    }
+    board
+        .try_play(from_uci_move(board, mv, chess960))
+        .map_err(drop)
+}
+
+/// Converts a move as played by `cozy_chess` into how UCI writes it.
+pub(crate) fn to_uci_move(board: &Board, mut mv: Move, chess960: bool) -> Move {
+    let color = board.side_to_move();
+    if chess960 || board.king(color) != mv.from || !board.colors(color).has(mv.to) {
+        return mv;
+    }
+    let first_rank = Rank::First.relative_to(color);
+    if mv.from == Square::new(File::E, first_rank) {
+        mv.to = match board.castle_rights(color).short {
+            Some(file) if Square::new(file, first_rank) == mv.to => {
+                Square::new(File::G, first_rank)
+            }
+            _ => Square::new(File::C, first_rank),
+        };
+    }
+    mv
+}
@@ -1,13 +1,44 @@
 use std::time::Duration;
 
 use crate::command::*;
+use crate::registry::{UciRegistry, EMPTY_REGISTRY};
 use crate::UciFormatOptions;
 
 use super::error::{UciParseError, UciParseErrorKind};
+#[cfg(feature = "extensions")]
+use super::extensions::{read_ext_command, EXT_COMMAND_KINDS};
 use super::stream::UciTokenStream;
 
+pub(super) const COMMAND_KINDS: &[&str] = &[
+    "uci",
+    "debug",
+    "isready",
+    "position",
+    "setoption",
+    "ucinewgame",
+    "stop",
+    "ponderhit",
+    "quit",
+    "go",
+];
+
 impl UciCommand {
+    /// Parses a command.
+    ///
+    /// A `setoption` name ends at the first `value` token unless a longer
+    /// name is listed in [`UciFormatOptions::option_names`], so the same
+    /// line may parse differently depending on the options the engine has
+    /// declared so far.
     pub fn parse_from(s: &str, options: &UciFormatOptions) -> Result<Self, UciParseError> {
+        Self::parse_with(s, options, &EMPTY_REGISTRY)
+    }
+
+    /// Parses like [`UciCommand::parse_from`], also accepting the commands in `registry`.
+    pub fn parse_with(
+        s: &str,
+        options: &UciFormatOptions,
+        registry: &UciRegistry,
+    ) -> Result<Self, UciParseError> {
         use UciParseErrorKind::*;
 
         let mut s = UciTokenStream::new(s);
@@ -32,12 +63,27 @@ impl UciCommand {
             }
             "setoption" => {
                 s.expect_token("name")?;
-                let name = s.read_string(|tok| matches!(tok, Some("value") | None))?;
-                let mut value = None;
-                if s.peek_token().is_ok() {
-                    s.expect_token("value")?;
-                    value = Some(s.read_string(|tok| tok.is_none())?);
-                }
+                let ends_name = |tok: Option<&str>| matches!(tok, Some("value") | None);
+                let read_value = |s: &mut UciTokenStream| -> Result<_, UciParseError> {
+                    let mut value = None;
+                    if s.peek_token().is_ok() {
+                        s.expect_token("value")?;
+                        value = Some(s.read_string(|tok| tok.is_none())?);
+                    }
+                    Ok(value)
+                };
+                // Prefer a name the engine declared, which may itself contain `value`.
+                let declared = |name: &str, tok: Option<&str>| {
+                    ends_name(tok) && options.option_names.iter().any(|n| n == name)
+                };
+                let mut stream = s.clone();
+                let (name, value) = match stream.read_string_then(declared, read_value) {
+                    Ok(declared) => {
+                        s = stream;
+                        declared
+                    }
+                    Err(_) => s.read_string_then(|_, tok| ends_name(tok), read_value)?,
+                };
                 Self::SetOption { name, value }
             }
             "ucinewgame" => Self::UciNewGame,
@@ -45,7 +91,12 @@ impl UciCommand {
             "ponderhit" => Self::PonderHit,
             "quit" => Self::Quit,
             "go" => Self::Go(read_go_params(&mut s)?),
-            cmd => Err(UnknownMessageKind(cmd.to_owned()).spans(cmd_span))?,
+            #[cfg(feature = "extensions")]
+            cmd if EXT_COMMAND_KINDS.contains(&cmd) => Self::Ext(read_ext_command(cmd, &mut s)?),
+            cmd => match registry.command(cmd) {
+                Some(parse) => Self::Custom(parse(&mut s, options)?),
+                None => Err(UnknownMessageKind(cmd.to_owned()).spans(cmd_span))?,
+            },
         };
         s.expect_end()?;
         Ok(cmd)
@@ -122,10 +173,10 @@ fn read_go_params(s: &mut UciTokenStream) -> Result<UciGoParams, UciParseError>
             }
             params.ponder = true;
         },
-        wtime -> Duration::from_millis(s.read_type()?),
-        btime -> Duration::from_millis(s.read_type()?),
-        winc -> Duration::from_millis(s.read_type()?),
-        binc -> Duration::from_millis(s.read_type()?),
+        wtime -> s.read_type()?,
+        btime -> s.read_type()?,
+        winc -> s.read_type()?,
+        binc -> s.read_type()?,
         movestogo -> s.read_type()?,
         depth -> s.read_type()?,
         nodes -> s.read_type()?,
@@ -137,6 +188,7 @@ fn read_go_params(s: &mut UciTokenStream) -> Result<UciGoParams, UciParseError>
             }
             params.infinite = true;
         },
+        perft -> s.read_type()?,
     }
     Ok(params)
 }
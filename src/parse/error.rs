@@ -21,8 +21,12 @@ pub enum UciParseErrorKind {
     UnexpectedEnd,
     #[error("unterminated string")]
     UnterminatedString,
+    #[error("line break in string")]
+    LineBreakInString,
     #[error("unknown message kind {0:?}")]
     UnknownMessageKind(String),
+    #[error("message kind {0:?} is both a command and a remark")]
+    AmbiguousMessageKind(String),
     #[error("duplicate field {0:?}")]
     DuplicateField(&'static str),
     #[error("unknown field {0:?}")]
@@ -40,7 +44,7 @@ pub enum UciParseErrorKind {
 }
 
 impl UciParseErrorKind {
-    pub(crate) fn spans(self, span: std::ops::Range<usize>) -> UciParseError {
+    pub fn spans(self, span: std::ops::Range<usize>) -> UciParseError {
         UciParseError { span, kind: self }
     }
 }
@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use crate::extensions::*;
+use crate::UciFormatOptions;
+
+use super::error::{UciParseError, UciParseErrorKind};
+use super::stream::UciTokenStream;
+use UciParseErrorKind::*;
+
+pub(super) const EXT_COMMAND_KINDS: &[&str] =
+    &["d", "eval", "bench", "flip", "compiler", "export_net"];
+
+pub(super) const EXT_REMARK_KINDS: &[&str] = &[
+    "Fen:",
+    "Key:",
+    "Checkers:",
+    "Final",
+    "Total",
+    "Nodes",
+    "Nodes/second",
+];
+
+pub(super) fn read_ext_command(
+    cmd: &str,
+    s: &mut UciTokenStream,
+) -> Result<UciExtCommand, UciParseError> {
+    Ok(match cmd {
+        "d" => UciExtCommand::D,
+        "eval" => UciExtCommand::Eval,
+        "bench" => UciExtCommand::Bench(read_bench_params(s)?),
+        "flip" => UciExtCommand::Flip,
+        "compiler" => UciExtCommand::Compiler,
+        "export_net" => {
+            let mut file = None;
+            if s.peek_token().is_ok() {
+                file = Some(s.read_string(|tok| tok.is_none())?);
+            }
+            UciExtCommand::ExportNet(file)
+        }
+        _ => unreachable!("not an extension command"),
+    })
+}
+
+fn read_bench_params(s: &mut UciTokenStream) -> Result<UciBenchParams, UciParseError> {
+    let mut params = UciBenchParams::default();
+    macro_rules! read_positional {
+        ($($field:ident => $body:expr,)*) => {$(
+            if s.peek_token().is_err() {
+                return Ok(params);
+            }
+            params.$field = Some($body);
+        )*};
+    }
+    read_positional! {
+        hash => s.read_type()?,
+        threads => s.read_type()?,
+        limit => s.read_type()?,
+        fen_file => s.read_token()?.0.to_owned(),
+        limit_type => match s.read_token()? {
+            ("depth", _) => UciBenchLimitType::Depth,
+            ("perft", _) => UciBenchLimitType::Perft,
+            ("nodes", _) => UciBenchLimitType::Nodes,
+            ("movetime", _) => UciBenchLimitType::Movetime,
+            ("eval", _) => UciBenchLimitType::Eval,
+            (tok, span) => Err(UnexpectedToken(tok.to_owned()).spans(span))?,
+        },
+    }
+    Ok(params)
+}
+
+pub(super) fn read_ext_remark(
+    rmk: &str,
+    s: &mut UciTokenStream,
+    options: &UciFormatOptions,
+) -> Result<UciExtRemark, UciParseError> {
+    Ok(match rmk {
+        "Fen:" => UciExtRemark::Fen(s.read_fen(options.chess960)?),
+        "Key:" => {
+            let (tok, span) = s.read_token()?;
+            let key = u64::from_str_radix(tok, 16).map_err(|e| IntParseError(e).spans(span))?;
+            UciExtRemark::Key(key)
+        }
+        "Checkers:" => {
+            let mut checkers = Vec::new();
+            while let Ok((tok, span)) = s.read_token() {
+                let square = tok
+                    .parse()
+                    .map_err(|_| InvalidField("Checkers").spans(span))?;
+                checkers.push(square);
+            }
+            UciExtRemark::Checkers(checkers)
+        }
+        "Final" => {
+            let (tok, span) = s.read_token()?;
+            if tok != "evaluation" && tok != "evaluation:" {
+                Err(UnexpectedToken(tok.to_owned()).spans(span))?;
+            }
+            if s.peek_token()?.0 == ":" {
+                let _ = s.read_token();
+            }
+            let (tok, span) = s.read_token()?;
+            let cp = match tok {
+                "none" => None,
+                tok => Some(parse_pawns(tok).ok_or(InvalidField("evaluation").spans(span))?),
+            };
+            let note = s.read_string(|tok| tok.is_none())?;
+            UciExtRemark::FinalEvaluation { cp, note }
+        }
+        "Total" => {
+            s.expect_token("time")?;
+            s.expect_token("(ms)")?;
+            s.expect_token(":")?;
+            UciExtRemark::TotalTime(Duration::from_millis(s.read_type()?))
+        }
+        "Nodes" => UciExtRemark::NodesSearched(super::read_nodes_searched(s)?),
+        "Nodes/second" => {
+            s.expect_token(":")?;
+            UciExtRemark::NodesPerSecond(s.read_type()?)
+        }
+        _ => unreachable!("not an extension remark"),
+    })
+}
+
+/// Parses an evaluation in pawns with up to two decimals, such as `+0.25`.
+fn parse_pawns(tok: &str) -> Option<i32> {
+    let (sign, digits) = match tok.strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, tok.strip_prefix('+').unwrap_or(tok)),
+    };
+    let (pawns, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if pawns.is_empty() || fraction.len() > 2 || !all_digits(pawns) || !all_digits(fraction) {
+        return None;
+    }
+    let fraction: i64 = format!("{:0<2}", fraction).parse().ok()?;
+    let pawns: i64 = pawns.parse().ok()?;
+    (sign * pawns.checked_mul(100)?.checked_add(fraction)?)
+        .try_into()
+        .ok()
+}
@@ -0,0 +1,43 @@
+use crate::command::UciCommand;
+use crate::message::UciMessage;
+use crate::registry::{UciRegistry, EMPTY_REGISTRY};
+use crate::remark::UciRemark;
+use crate::UciFormatOptions;
+
+use super::command::COMMAND_KINDS;
+use super::error::{UciParseError, UciParseErrorKind};
+#[cfg(feature = "extensions")]
+use super::extensions::{EXT_COMMAND_KINDS, EXT_REMARK_KINDS};
+use super::remark::REMARK_KINDS;
+use super::stream::UciTokenStream;
+use UciParseErrorKind::*;
+
+impl UciMessage {
+    /// Parses a command or a remark, depending on the first token.
+    pub fn parse_from(s: &str, options: &UciFormatOptions) -> Result<Self, UciParseError> {
+        Self::parse_with(s, options, &EMPTY_REGISTRY)
+    }
+
+    /// Parses like [`UciMessage::parse_from`], also accepting the commands
+    /// and remarks in `registry`.
+    pub fn parse_with(
+        s: &str,
+        options: &UciFormatOptions,
+        registry: &UciRegistry,
+    ) -> Result<Self, UciParseError> {
+        let (kind, span) = UciTokenStream::new(s).read_token()?;
+        let command = COMMAND_KINDS.contains(&kind) || registry.is_command(kind);
+        let remark = REMARK_KINDS.contains(&kind) || registry.is_remark(kind);
+        #[cfg(feature = "extensions")]
+        let (command, remark) = (
+            command || EXT_COMMAND_KINDS.contains(&kind),
+            remark || EXT_REMARK_KINDS.contains(&kind),
+        );
+        match (command, remark) {
+            (true, false) => Ok(Self::Command(UciCommand::parse_with(s, options, registry)?)),
+            (false, true) => Ok(Self::Remark(UciRemark::parse_with(s, options, registry)?)),
+            (true, true) => Err(AmbiguousMessageKind(kind.to_owned()).spans(span)),
+            (false, false) => Err(UnknownMessageKind(kind.to_owned()).spans(span)),
+        }
+    }
+}
@@ -1,6 +1,23 @@
 pub mod command;
 mod error;
+#[cfg(feature = "extensions")]
+mod extensions;
+pub mod message;
 pub mod remark;
-mod stream;
+pub(crate) mod stream;
 
 pub use error::{UciParseError, UciParseErrorKind};
+pub use stream::UciTokenStream;
+
+/// Reads the count of a `Nodes searched: 197281` line after its first word,
+/// as printed by `go perft` and `bench`. `bench` pads the label, so the
+/// colon may be a separate token, and some engines leave out `searched`.
+pub(crate) fn read_nodes_searched(s: &mut UciTokenStream) -> Result<u64, UciParseError> {
+    if let ("searched" | "searched:", _) = s.peek_token()? {
+        s.read_token()?;
+    }
+    if s.peek_token()?.0 == ":" {
+        s.read_token()?;
+    }
+    s.read_type()
+}
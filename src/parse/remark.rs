@@ -1,14 +1,28 @@
 use std::time::Duration;
 
+use crate::registry::{UciRegistry, EMPTY_REGISTRY};
 use crate::remark::*;
 use crate::UciFormatOptions;
 
 use super::error::{UciParseError, UciParseErrorKind};
+#[cfg(feature = "extensions")]
+use super::extensions::{read_ext_remark, EXT_REMARK_KINDS};
 use super::stream::UciTokenStream;
 use UciParseErrorKind::*;
 
+pub(super) const REMARK_KINDS: &[&str] = &["id", "uciok", "readyok", "bestmove", "info", "option"];
+
 impl UciRemark {
     pub fn parse_from(s: &str, options: &UciFormatOptions) -> Result<Self, UciParseError> {
+        Self::parse_with(s, options, &EMPTY_REGISTRY)
+    }
+
+    /// Parses like [`UciRemark::parse_from`], also accepting the remarks in `registry`.
+    pub fn parse_with(
+        s: &str,
+        options: &UciFormatOptions,
+        registry: &UciRegistry,
+    ) -> Result<Self, UciParseError> {
         let mut s = UciTokenStream::new(s);
         let (rmk, rmk_span) = s.read_token()?;
         let rmk = match rmk {
@@ -20,7 +34,11 @@ impl UciRemark {
             "uciok" => Self::UciOk,
             "readyok" => Self::ReadyOk,
             "bestmove" => {
-                let mv = s.read_type()?;
+                let mv = match s.peek_token()?.0 {
+                    "(none)" => s.read_token().map(|_| UciBestMove::None)?,
+                    "0000" => s.read_token().map(|_| UciBestMove::Null)?,
+                    _ => UciBestMove::Move(s.read_type()?),
+                };
                 let ponder = match s.read_token() {
                     Ok(("ponder", _)) => Some(s.read_type()?),
                     Ok((tok, span)) => Err(UnexpectedToken(tok.to_owned()).spans(span))?,
@@ -31,11 +49,25 @@ impl UciRemark {
             "info" => Self::Info(read_info(&mut s, options)?),
             "option" => {
                 s.expect_token("name")?;
-                let name = s.read_string(|tok| tok == Some("type"))?;
-                let info = read_option_info(&mut s)?;
+                // The name ends at the first `type` that the rest parses after.
+                let (name, info) = s.read_string_then(
+                    |_, tok| tok == Some("type"),
+                    |s| {
+                        let info = read_option_info(s)?;
+                        s.expect_end()?;
+                        Ok(info)
+                    },
+                )?;
                 Self::Option { name, info }
             }
-            rmk => Err(UnknownMessageKind(rmk.to_owned()).spans(rmk_span))?,
+            #[cfg(feature = "extensions")]
+            rmk if EXT_REMARK_KINDS.contains(&rmk) => {
+                Self::Ext(read_ext_remark(rmk, &mut s, options)?)
+            }
+            rmk => match registry.remark(rmk) {
+                Some(parse) => Self::Custom(parse(&mut s, options)?),
+                None => Err(UnknownMessageKind(rmk.to_owned()).spans(rmk_span))?,
+            },
         };
         s.expect_end()?;
         Ok(rmk)
@@ -87,7 +119,11 @@ fn read_info(s: &mut UciTokenStream, options: &UciFormatOptions) -> Result<UciIn
         refutation => s.read_moves(),
         currline => {
             let mut cpu = None;
-            if let Ok(Ok(num)) = s.peek_token().map(|(tok, _)| tok.parse()) {
+            // `0000` is the null move rather than a CPU number.
+            if let Ok(Ok(num)) = s.peek_token().map(|(tok, _)| match tok {
+                "0000" => Err(()),
+                tok => tok.parse().map_err(drop),
+            }) {
                 let _ = s.read_token();
                 cpu = Some(num);
             }
@@ -97,7 +133,7 @@ fn read_info(s: &mut UciTokenStream, options: &UciFormatOptions) -> Result<UciIn
     }
 }
 
-fn read_option_info(s: &mut UciTokenStream) -> Result<UciOptionInfo, UciParseError> {
+pub(crate) fn read_option_info(s: &mut UciTokenStream) -> Result<UciOptionInfo, UciParseError> {
     s.expect_token("type")?;
     let (tok, span) = s.read_token()?;
     Ok(match tok {
@@ -117,11 +153,13 @@ fn read_option_info(s: &mut UciTokenStream) -> Result<UciOptionInfo, UciParseErr
         }
         "combo" => {
             s.expect_token("default")?;
-            let default = s.read_token()?.0.to_owned();
+            let read_label =
+                |s: &mut UciTokenStream| s.read_string(|tok| matches!(tok, Some("var") | None));
+            let default = read_label(s)?;
             let mut labels = Vec::new();
             while s.peek_token().is_ok() {
                 s.expect_token("var")?;
-                labels.push(s.read_token()?.0.to_owned());
+                labels.push(read_label(s)?);
             }
             UciOptionInfo::Combo { default, labels }
         }
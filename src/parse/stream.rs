@@ -6,8 +6,12 @@ use std::str::SplitAsciiWhitespace;
 use cozy_chess::{Board, Move};
 
 use super::error::{UciParseError, UciParseErrorKind};
+use crate::UCI_NULL_MOVE;
 use UciParseErrorKind::*;
 
+/// The whitespace-separated tokens of a line, with the byte span of each
+/// token for error reporting.
+#[derive(Clone)]
 pub struct UciTokenStream<'s> {
     str: &'s str,
     iter: Peekable<SplitAsciiWhitespace<'s>>,
@@ -49,7 +53,60 @@ impl<'s> UciTokenStream<'s> {
                 .map_err(|_| UnterminatedString.spans(start..self.str.len()))?;
             end = span.end;
         }
-        Ok(self.str[start..end].to_owned())
+        let string = &self.str[start..end];
+        if string.contains(['\n', '\r']) {
+            return Err(LineBreakInString.spans(start..end));
+        }
+        Ok(string.to_owned())
+    }
+
+    /// Reads the shortest string for which `rest` succeeds on the remainder
+    /// of the stream, trying every token boundary where `terminates` holds
+    /// for the string so far and the next token. If no split works, the
+    /// error that got furthest into the input is returned. Line breaks fail
+    /// immediately, since they would otherwise make a split depend on
+    /// whitespace.
+    pub fn read_string_then<T>(
+        &mut self,
+        terminates: impl Fn(&str, Option<&str>) -> bool,
+        mut rest: impl FnMut(&mut Self) -> Result<T, UciParseError>,
+    ) -> Result<(String, T), UciParseError> {
+        let start = self.curr_tok_span().start;
+        let mut end = start;
+        let mut stream = self.clone();
+        let mut furthest: Option<UciParseError> = None;
+        loop {
+            let string = &self.str[start..end];
+            if terminates(string, stream.iter.peek().copied()) {
+                if string.contains(['\n', '\r']) {
+                    return Err(LineBreakInString.spans(start..end));
+                }
+                let mut remainder = stream.clone();
+                match rest(&mut remainder) {
+                    Ok(value) => {
+                        *self = remainder;
+                        return Ok((string.to_owned(), value));
+                    }
+                    Err(e) if matches!(e.kind, LineBreakInString) => return Err(e),
+                    Err(e) => {
+                        if furthest
+                            .as_ref()
+                            .is_none_or(|f| e.span.start > f.span.start)
+                        {
+                            furthest = Some(e);
+                        }
+                    }
+                }
+            }
+            match stream.read_token() {
+                Ok((_, span)) => end = span.end,
+                Err(_) => {
+                    return Err(
+                        furthest.unwrap_or_else(|| UnterminatedString.spans(start..self.str.len()))
+                    )
+                }
+            }
+        }
     }
 
     pub fn read_type<T: FromStr>(&mut self) -> Result<T, UciParseError>
@@ -83,7 +140,14 @@ impl<'s> UciTokenStream<'s> {
 
     pub fn read_moves(&mut self) -> Vec<Move> {
         let mut moves = Vec::new();
-        while let Ok(Ok(mv)) = self.peek_token().map(|(tok, _)| tok.parse()) {
+        while let Ok((tok, _)) = self.peek_token() {
+            let mv = match tok {
+                "0000" => UCI_NULL_MOVE,
+                tok => match tok.parse() {
+                    Ok(mv) => mv,
+                    Err(_) => break,
+                },
+            };
             let _ = self.read_token();
             moves.push(mv);
         }
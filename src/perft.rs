@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use cozy_chess::{Board, Move};
+use thiserror::*;
+
+use crate::command::{UciCommand, UciGoParams, UciInitPos};
+use crate::engine::{UciEngine, UciEngineError};
+use crate::moves::{play_uci_move, to_uci_move};
+use crate::parse::read_nodes_searched;
+use crate::parse::stream::UciTokenStream;
+use crate::remark::UciRemark;
+use crate::validate::{UciIllegalMoveError, UciMoveValidator};
+use crate::{UciParseError, UciParseErrorKind};
+
+/// A line printed by `go perft`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UciDivideLine {
+    /// `e2e4: 20`, the leaf nodes below a root move.
+    Move(Move, u64),
+    /// `Nodes searched: 197281`, the total that ends the output.
+    Nodes(u64),
+}
+
+impl UciDivideLine {
+    pub fn parse_from(s: &str) -> Result<Self, UciParseError> {
+        use UciParseErrorKind::*;
+
+        let mut s = UciTokenStream::new(s);
+        let (tok, span) = s.read_token()?;
+        let line = match tok.trim_end_matches(':') {
+            "Nodes" | "Total" => Self::Nodes(read_nodes_searched(&mut s)?),
+            mv => match mv.parse() {
+                Ok(mv) => Self::Move(mv, read_count(&mut s)?),
+                Err(_) => Err(UnknownMessageKind(tok.to_owned()).spans(span))?,
+            },
+        };
+        s.expect_end()?;
+        Ok(line)
+    }
+}
+
+/// Reads a count, which may be preceded by a separate `:`.
+fn read_count(s: &mut UciTokenStream) -> Result<u64, UciParseError> {
+    if s.peek_token()?.0 == ":" {
+        s.read_token()?;
+    }
+    s.read_type()
+}
+
+/// The leaf node count below each root move, as printed by `go perft`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UciPerftDivide {
+    pub moves: Vec<(Move, u64)>,
+    pub nodes: u64,
+}
+
+impl UciPerftDivide {
+    /// Computes the divide with `cozy_chess`, with moves written as in UCI.
+    pub fn compute(board: &Board, depth: u32, chess960: bool) -> Self {
+        let mut divide = Self::default();
+        board.generate_moves(|mvs| {
+            for mv in mvs {
+                let mut child = board.clone();
+                child.play_unchecked(mv);
+                let nodes = perft(&child, depth.saturating_sub(1));
+                divide.moves.push((to_uci_move(board, mv, chess960), nodes));
+                divide.nodes += nodes;
+            }
+            false
+        });
+        divide
+    }
+
+    /// Receives divide output from an engine, up to and including the
+    /// total. Blank lines and UCI remarks such as `info string` are skipped.
+    pub fn recv(engine: &mut UciEngine, timeout: Duration) -> Result<Self, UciEngineError> {
+        let mut divide = Self::default();
+        loop {
+            let line = engine.recv_line(timeout)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match UciDivideLine::parse_from(&line) {
+                Ok(UciDivideLine::Move(mv, nodes)) => divide.moves.push((mv, nodes)),
+                Ok(UciDivideLine::Nodes(nodes)) => {
+                    divide.nodes = nodes;
+                    return Ok(divide);
+                }
+                Err(_) if UciRemark::parse_from(&line, &engine.options).is_ok() => {}
+                Err(error) => return Err(UciEngineError::Parse { line, error }),
+            }
+        }
+    }
+}
+
+/// Counts the leaf nodes `depth` plies below `board`.
+pub fn perft(board: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut nodes = 0;
+    board.generate_moves(|mvs| {
+        if depth == 1 {
+            nodes += mvs.len() as u64;
+        } else {
+            for mv in mvs {
+                let mut child = board.clone();
+                child.play_unchecked(mv);
+                nodes += perft(&child, depth - 1);
+            }
+        }
+        false
+    });
+    nodes
+}
+
+#[derive(Debug, Error)]
+pub enum UciPerftError {
+    #[error(transparent)]
+    Engine(#[from] UciEngineError),
+    #[error(transparent)]
+    IllegalMove(#[from] UciIllegalMoveError),
+    #[error("perft depth must be at least 1")]
+    ZeroDepth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UciPerftMismatchKind {
+    /// A legal move is missing from the engine's divide.
+    Missing(Move),
+    /// The engine's divide contains a move that is not legal.
+    Illegal(Move),
+    /// The engine's divide contains a move more than once.
+    Duplicate(Move),
+    /// The engine counts a different number of nodes below a move.
+    Nodes {
+        mv: Move,
+        expected: u64,
+        actual: u64,
+    },
+    /// The moves agree, but the engine's total does not.
+    Total { expected: u64, actual: u64 },
+}
+
+/// The first disagreement between an engine's divide and `cozy_chess`,
+/// narrowed down as far as possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UciPerftMismatch {
+    /// Moves leading from the starting position to `board`.
+    pub moves: Vec<Move>,
+    pub board: Board,
+    pub depth: u32,
+    pub kind: UciPerftMismatchKind,
+}
+
+/// Runs `go perft` on the engine and compares the divide against
+/// `cozy_chess`. When the count below a move differs, the search recurses
+/// into that move with one ply less, until the mismatch can be pinned on a
+/// single move or on the engine's total. If the divide below the move
+/// agrees, the mismatch is reported on the move itself.
+pub fn find_perft_mismatch(
+    engine: &mut UciEngine,
+    init_pos: &UciInitPos,
+    moves: &[Move],
+    depth: u32,
+    timeout: Duration,
+) -> Result<Option<UciPerftMismatch>, UciPerftError> {
+    let chess960 = engine.options.chess960;
+    let mut board = UciMoveValidator::new(init_pos, moves, &engine.options)?
+        .root()
+        .clone();
+    if depth == 0 {
+        return Err(UciPerftError::ZeroDepth);
+    }
+    let mut path = Vec::new();
+    let mut depth = depth;
+    // The mismatch in the node count of the move being recursed into.
+    let mut parent = None;
+    loop {
+        let mut position_moves = moves.to_vec();
+        position_moves.extend(&path);
+        engine
+            .send(&UciCommand::Position {
+                init_pos: init_pos.clone(),
+                moves: position_moves,
+            })
+            .map_err(UciEngineError::from)?;
+        engine
+            .send(&UciCommand::Go(UciGoParams {
+                perft: Some(depth),
+                ..Default::default()
+            }))
+            .map_err(UciEngineError::from)?;
+        let actual = UciPerftDivide::recv(engine, timeout)?;
+        let expected = UciPerftDivide::compute(&board, depth, chess960);
+
+        let mismatch = |kind| {
+            Ok(Some(UciPerftMismatch {
+                moves: path.clone(),
+                board: board.clone(),
+                depth,
+                kind,
+            }))
+        };
+        let mut actual_moves = HashMap::new();
+        for &(mv, nodes) in &actual.moves {
+            if actual_moves.insert(mv, nodes).is_some() {
+                return mismatch(UciPerftMismatchKind::Duplicate(mv));
+            }
+        }
+        for &(mv, _) in &actual.moves {
+            if !expected.moves.iter().any(|&(m, _)| m == mv) {
+                return mismatch(UciPerftMismatchKind::Illegal(mv));
+            }
+        }
+        let mut wrong_move = None;
+        for &(mv, nodes) in &expected.moves {
+            match actual_moves.get(&mv) {
+                None => return mismatch(UciPerftMismatchKind::Missing(mv)),
+                Some(&actual) if actual != nodes && wrong_move.is_none() => {
+                    wrong_move = Some((mv, nodes, actual))
+                }
+                Some(_) => {}
+            }
+        }
+        match wrong_move {
+            Some((mv, expected, actual)) if depth == 1 => {
+                return mismatch(UciPerftMismatchKind::Nodes {
+                    mv,
+                    expected,
+                    actual,
+                })
+            }
+            Some((mv, expected, actual)) => {
+                parent = mismatch(UciPerftMismatchKind::Nodes {
+                    mv,
+                    expected,
+                    actual,
+                })?;
+                play_uci_move(&mut board, mv, chess960).expect("move is legal");
+                path.push(mv);
+                depth -= 1;
+            }
+            None if actual.nodes != expected.nodes => {
+                return mismatch(UciPerftMismatchKind::Total {
+                    expected: expected.nodes,
+                    actual: actual.nodes,
+                })
+            }
+            None => return Ok(parent),
+        }
+    }
+}
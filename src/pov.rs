@@ -0,0 +1,112 @@
+use cozy_chess::{Board, Color, Piece};
+
+use crate::command::UciCommand;
+use crate::moves::play_uci_move;
+use crate::remark::{UciRemark, UciScoreKind, UciScoreValue};
+use crate::UciFormatOptions;
+
+const PIECE_VALUES: [(Piece, i32); 5] = [
+    (Piece::Pawn, 100),
+    (Piece::Knight, 300),
+    (Piece::Bishop, 300),
+    (Piece::Rook, 500),
+    (Piece::Queen, 900),
+];
+
+/// Material imbalance needed before a position counts as evidence.
+const MIN_IMBALANCE: i32 = 300;
+/// Smallest centipawn score that counts as evidence.
+const MIN_CP: i32 = 100;
+/// Number of observations needed before giving a verdict.
+const MIN_EVIDENCE: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UciScorePov {
+    SideToMove,
+    White,
+}
+
+/// Detects engines that report scores from White's point of view instead
+/// of the side to move's.
+///
+/// Only searches with Black to move in clearly unbalanced positions are
+/// informative: a score that agrees in sign with White's material balance
+/// rather than Black's counts towards [`UciScorePov::White`].
+#[derive(Debug, Clone, Default)]
+pub struct UciPovDetector {
+    options: UciFormatOptions,
+    board: Option<Board>,
+    /// Scores consistent with the side to move's point of view.
+    pub side_to_move: u32,
+    /// Scores consistent with White's point of view.
+    pub white: u32,
+}
+
+impl UciPovDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update_command(&mut self, cmd: &UciCommand) {
+        self.options.update(cmd);
+        if let UciCommand::Position { init_pos, moves } = cmd {
+            let mut board: Board = init_pos.clone().into();
+            let legal = moves
+                .iter()
+                .all(|&mv| play_uci_move(&mut board, mv, self.options.chess960).is_ok());
+            self.board = legal.then_some(board);
+        }
+    }
+
+    pub fn update_remark(&mut self, rmk: &UciRemark) {
+        let (Some(board), UciRemark::Info(info)) = (&self.board, rmk) else {
+            return;
+        };
+        let Some(score) = info.score.filter(|s| s.kind == UciScoreKind::Exact) else {
+            return;
+        };
+        if board.side_to_move() != Color::Black {
+            return;
+        }
+        let sign = match score.value() {
+            Some(UciScoreValue::Cp(cp)) if cp.abs() >= MIN_CP => cp.signum(),
+            Some(UciScoreValue::Mate(moves)) if moves != 0 => moves.signum(),
+            _ => return,
+        };
+        let material = material(board, Color::Black);
+        if material.abs() < MIN_IMBALANCE {
+            return;
+        }
+        match sign == material.signum() {
+            true => self.side_to_move += 1,
+            false => self.white += 1,
+        }
+    }
+
+    /// The point of view the engine appears to use, once there is enough
+    /// evidence for a clear majority.
+    pub fn verdict(&self) -> Option<UciScorePov> {
+        if self.side_to_move + self.white < MIN_EVIDENCE {
+            return None;
+        }
+        if self.white > 2 * self.side_to_move {
+            Some(UciScorePov::White)
+        } else if self.side_to_move > 2 * self.white {
+            Some(UciScorePov::SideToMove)
+        } else {
+            None
+        }
+    }
+}
+
+fn material(board: &Board, pov: Color) -> i32 {
+    PIECE_VALUES
+        .iter()
+        .map(|&(piece, value)| {
+            let pieces = board.pieces(piece);
+            let ours = (pieces & board.colors(pov)).len() as i32;
+            let theirs = (pieces & board.colors(!pov)).len() as i32;
+            (ours - theirs) * value
+        })
+        .sum()
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use cozy_chess::{Board, Move};
+
+use crate::moves::play_uci_move;
+use crate::remark::{UciInfo, UciScore};
+use crate::UciFormatOptions;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SearchAnomaly {
+    NodesDecreased {
+        previous: u64,
+        current: u64,
+    },
+    TimeDecreased {
+        previous: Duration,
+        current: Duration,
+    },
+    IllegalPvMove {
+        mv: Move,
+    },
+}
+
+/// Accumulated state of a single search, built from the `info` remarks an
+/// engine sends while searching from `root`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchProgress {
+    pub root: Board,
+    pub pv: Vec<Move>,
+    pub score: Option<UciScore>,
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub nps: Option<u64>,
+    pub tbhits: Option<u64>,
+    pub hashfull: Option<u16>,
+    pub currmove: Option<Move>,
+    pub currmovenumber: Option<u8>,
+    pub time: Option<Duration>,
+    pub score_history: BTreeMap<u32, UciScore>,
+    pub anomalies: Vec<SearchAnomaly>,
+    chess960: bool,
+}
+
+impl SearchProgress {
+    pub fn new(root: Board, options: &UciFormatOptions) -> Self {
+        Self {
+            root,
+            pv: Vec::new(),
+            score: None,
+            depth: None,
+            seldepth: None,
+            nodes: None,
+            nps: None,
+            tbhits: None,
+            hashfull: None,
+            currmove: None,
+            currmovenumber: None,
+            time: None,
+            score_history: BTreeMap::new(),
+            anomalies: Vec::new(),
+            chess960: options.chess960,
+        }
+    }
+
+    pub fn update(&mut self, info: &UciInfo) {
+        use SearchAnomaly::*;
+
+        if let (Some(previous), Some(current)) = (self.nodes, info.nodes) {
+            if current < previous {
+                self.anomalies.push(NodesDecreased { previous, current });
+            }
+        }
+        if let (Some(previous), Some(current)) = (self.time, info.time) {
+            if current < previous {
+                self.anomalies.push(TimeDecreased { previous, current });
+            }
+        }
+
+        macro_rules! update_fields {
+            ($($field:ident),*) => {$(
+                if let Some(value) = info.$field {
+                    self.$field = Some(value);
+                }
+            )*};
+        }
+        update_fields!(
+            depth,
+            seldepth,
+            nodes,
+            tbhits,
+            hashfull,
+            currmove,
+            currmovenumber,
+            time
+        );
+
+        self.nps = match (info.nps, self.nodes, self.time) {
+            (Some(nps), _, _) => Some(nps),
+            (None, Some(nodes), Some(time)) if time.as_millis() > 0 => {
+                Some((nodes as u128 * 1000 / time.as_millis()) as u64)
+            }
+            (None, _, _) => self.nps,
+        };
+
+        if info.multipv.unwrap_or(1) != 1 {
+            return;
+        }
+        if let Some(pv) = &info.pv {
+            if let Some(&mv) = pv.first() {
+                if play_uci_move(&mut self.root.clone(), mv, self.chess960).is_err() {
+                    self.anomalies.push(IllegalPvMove { mv });
+                }
+            }
+            self.pv = pv.clone();
+        }
+        if let Some(score) = info.score {
+            self.score = Some(score);
+            if let Some(depth) = info.depth {
+                self.score_history.insert(depth, score);
+            }
+        }
+    }
+
+    pub fn best_move(&self) -> Option<Move> {
+        self.pv.first().copied()
+    }
+}
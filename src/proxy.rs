@@ -0,0 +1,143 @@
+//! Forwarding traffic between a GUI and an engine while recording it.
+
+use std::io;
+use std::io::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use crate::command::UciCommand;
+use crate::remark::UciRemark;
+use crate::UciFormatOptions;
+
+/// Writes a transcript in the format of `tests/uci_logs`: commands start
+/// with `>` and remarks with `<`. Each line is preceded by a `#` comment
+/// with the seconds since the transcript started, followed by the parse
+/// error if the line does not parse. Log readers skip `#` lines, so the
+/// transcript replays like any other log.
+pub struct UciTranscript<W> {
+    writer: W,
+    start: Instant,
+    options: UciFormatOptions,
+    failed: bool,
+}
+
+impl<W: Write> UciTranscript<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            start: Instant::now(),
+            options: UciFormatOptions::default(),
+            failed: false,
+        }
+    }
+
+    pub fn command(&mut self, line: &str) -> io::Result<()> {
+        let error = match UciCommand::parse_from(line, &self.options) {
+            Ok(cmd) => {
+                self.options.update(&cmd);
+                None
+            }
+            Err(error) => Some(error),
+        };
+        self.record('>', line, error)
+    }
+
+    pub fn remark(&mut self, line: &str) -> io::Result<()> {
+        let error = match UciRemark::parse_from(line, &self.options) {
+            Ok(rmk) => {
+                self.options.update_from_remark(&rmk);
+                None
+            }
+            Err(error) => Some(error),
+        };
+        self.record('<', line, error)
+    }
+
+    /// Writes a free-form `#` comment.
+    pub fn comment(&mut self, comment: &str) -> io::Result<()> {
+        for line in comment.lines() {
+            writeln!(self.writer, "# {}", line)?;
+        }
+        self.writer.flush()
+    }
+
+    fn record(
+        &mut self,
+        direction: char,
+        line: &str,
+        error: Option<crate::UciParseError>,
+    ) -> io::Result<()> {
+        let elapsed = self.start.elapsed();
+        write!(
+            self.writer,
+            "# {}.{:03}",
+            elapsed.as_secs(),
+            elapsed.subsec_millis()
+        )?;
+        if let Some(error) = error {
+            write!(self.writer, " {}", error)?;
+        }
+        writeln!(self.writer)?;
+        writeln!(self.writer, "{}{}", direction, line)?;
+        self.writer.flush()
+    }
+
+    /// Records a line, giving up on the transcript after the first error,
+    /// which is reported on stderr.
+    fn record_best_effort(&mut self, record: impl FnOnce(&mut Self) -> io::Result<()>) {
+        if self.failed {
+            return;
+        }
+        if let Err(err) = record(self) {
+            self.failed = true;
+            eprintln!("failed to write transcript, no longer recording: {}", err);
+        }
+    }
+}
+
+/// Forwards lines from the GUI to the engine and back unchanged, recording
+/// both directions in the transcript. Returns once the engine closes its
+/// output; forwarding from the GUI continues on a detached thread.
+///
+/// Recording is best-effort: if writing the transcript fails, the error is
+/// reported once on stderr and forwarding carries on without it.
+pub fn run_proxy<T: Write + Send + 'static>(
+    gui_reader: impl BufRead + Send + 'static,
+    gui_writer: impl Write,
+    engine_reader: impl BufRead,
+    engine_writer: impl Write + Send + 'static,
+    transcript: UciTranscript<T>,
+) -> io::Result<()> {
+    let transcript = Arc::new(Mutex::new(transcript));
+    let commands = {
+        let transcript = Arc::clone(&transcript);
+        move |line: &str| {
+            let mut transcript = transcript.lock().unwrap();
+            transcript.record_best_effort(|t| t.command(line))
+        }
+    };
+    thread::spawn(move || forward(gui_reader, engine_writer, commands));
+    forward(engine_reader, gui_writer, |line| {
+        let mut transcript = transcript.lock().unwrap();
+        transcript.record_best_effort(|t| t.remark(line))
+    })
+}
+
+fn forward(
+    mut reader: impl BufRead,
+    mut writer: impl Write,
+    mut record: impl FnMut(&str),
+) -> io::Result<()> {
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(());
+        }
+        let line = String::from_utf8_lossy(&buf);
+        record(line.trim_end_matches(['\n', '\r']));
+        writer.write_all(&buf)?;
+        writer.flush()?;
+    }
+}
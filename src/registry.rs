@@ -0,0 +1,170 @@
+//! Engine-specific commands and remarks outside the UCI protocol.
+
+use std::any::Any;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+
+use crate::{UciFormatError, UciFormatOptions, UciParseError, UciTokenStream};
+
+/// A message starting with a keyword of its own, such as an engine's private
+/// `tune` command. Register the type with [`UciRegistry`] so that it parses.
+pub trait UciExtension: UciExtensionDyn + Debug + Send + Sync {
+    /// The keyword that starts the message.
+    fn kind() -> &'static str
+    where
+        Self: Sized;
+
+    /// Parses the rest of the line after the keyword. Tokens left over are
+    /// reported as unexpected.
+    fn parse(s: &mut UciTokenStream, options: &UciFormatOptions) -> Result<Self, UciParseError>
+    where
+        Self: Sized;
+
+    /// Writes the rest of the line after the keyword, each token preceded
+    /// by a space.
+    fn format_args(&self, f: &mut Formatter) -> fmt::Result;
+
+    /// Fails if the message cannot be formatted so that it parses back.
+    fn check(&self) -> Result<(), UciFormatError> {
+        Ok(())
+    }
+}
+
+/// Object-safe counterparts of `Clone`, `PartialEq` and `Hash`, implemented
+/// for every suitable [`UciExtension`].
+pub trait UciExtensionDyn: Any {
+    fn dyn_kind(&self) -> &'static str;
+    fn dyn_clone(&self) -> Box<dyn UciExtension>;
+    fn dyn_eq(&self, other: &dyn UciExtension) -> bool;
+    fn dyn_hash(&self, state: &mut dyn Hasher);
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: UciExtension + Clone + Eq + Hash> UciExtensionDyn for T {
+    fn dyn_kind(&self) -> &'static str {
+        T::kind()
+    }
+
+    fn dyn_clone(&self) -> Box<dyn UciExtension> {
+        Box::new(self.clone())
+    }
+
+    fn dyn_eq(&self, other: &dyn UciExtension) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+
+    fn dyn_hash(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl dyn UciExtension {
+    pub fn downcast_ref<T: UciExtension>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+}
+
+impl Clone for Box<dyn UciExtension> {
+    fn clone(&self) -> Self {
+        self.dyn_clone()
+    }
+}
+
+impl PartialEq for dyn UciExtension {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn UciExtension {}
+
+impl Hash for dyn UciExtension {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dyn_kind().hash(state);
+        self.dyn_hash(state);
+    }
+}
+
+type ParseFn =
+    fn(&mut UciTokenStream, &UciFormatOptions) -> Result<Box<dyn UciExtension>, UciParseError>;
+
+fn parse_boxed<T: UciExtension + 'static>(
+    s: &mut UciTokenStream,
+    options: &UciFormatOptions,
+) -> Result<Box<dyn UciExtension>, UciParseError> {
+    Ok(Box::new(T::parse(s, options)?))
+}
+
+/// The extension commands and remarks to parse, keyed by keyword, passed to
+/// the `parse_with` functions. Built-in keywords always take precedence.
+#[derive(Clone, Default)]
+pub struct UciRegistry {
+    commands: Vec<(&'static str, ParseFn)>,
+    remarks: Vec<(&'static str, ParseFn)>,
+}
+
+/// The registry used by the `parse_from` functions.
+pub(crate) static EMPTY_REGISTRY: UciRegistry = UciRegistry::new();
+
+impl UciRegistry {
+    pub const fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            remarks: Vec::new(),
+        }
+    }
+
+    /// Registers a command, replacing any earlier one with the same keyword.
+    pub fn register_command<T: UciExtension + 'static>(&mut self) {
+        register::<T>(&mut self.commands);
+    }
+
+    /// Registers a remark, replacing any earlier one with the same keyword.
+    pub fn register_remark<T: UciExtension + 'static>(&mut self) {
+        register::<T>(&mut self.remarks);
+    }
+
+    pub fn is_command(&self, kind: &str) -> bool {
+        self.command(kind).is_some()
+    }
+
+    pub fn is_remark(&self, kind: &str) -> bool {
+        self.remark(kind).is_some()
+    }
+
+    pub(crate) fn command(&self, kind: &str) -> Option<ParseFn> {
+        lookup(&self.commands, kind)
+    }
+
+    pub(crate) fn remark(&self, kind: &str) -> Option<ParseFn> {
+        lookup(&self.remarks, kind)
+    }
+}
+
+fn register<T: UciExtension + 'static>(parsers: &mut Vec<(&'static str, ParseFn)>) {
+    parsers.retain(|&(kind, _)| kind != T::kind());
+    parsers.push((T::kind(), parse_boxed::<T>));
+}
+
+fn lookup(parsers: &[(&'static str, ParseFn)], kind: &str) -> Option<ParseFn> {
+    parsers
+        .iter()
+        .find(|&&(k, _)| k == kind)
+        .map(|&(_, parse)| parse)
+}
+
+impl Debug for UciRegistry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let kinds = |parsers: &[(&'static str, ParseFn)]| {
+            parsers.iter().map(|&(k, _)| k).collect::<Vec<_>>()
+        };
+        f.debug_struct("UciRegistry")
+            .field("commands", &kinds(&self.commands))
+            .field("remarks", &kinds(&self.remarks))
+            .finish()
+    }
+}
@@ -1,6 +1,11 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::Neg;
 use std::time::Duration;
 
-use cozy_chess::Move;
+use cozy_chess::{Board, Color, Move};
+
+use crate::{UciFormatError, UciFormatErrorKind};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum UciIdInfo {
@@ -23,6 +28,116 @@ pub enum UciScoreKind {
     UpperBound,
 }
 
+/// A score from the point of view of the side to move.
+///
+/// Scores are ordered from worst to best: being mated sooner, being mated
+/// later, centipawns, mating later, mating sooner. `Mate(0)` means the side
+/// to move is already mated and ranks below everything else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UciScoreValue {
+    Cp(i32),
+    Mate(i32),
+}
+
+impl UciScoreValue {
+    pub fn is_mate(&self) -> bool {
+        matches!(self, Self::Mate(_))
+    }
+
+    fn rank(&self) -> (u8, i64) {
+        match *self {
+            Self::Mate(moves) if moves <= 0 => (0, -(moves as i64)),
+            Self::Cp(cp) => (1, cp as i64),
+            Self::Mate(moves) => (2, -(moves as i64)),
+        }
+    }
+}
+
+impl Ord for UciScoreValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for UciScoreValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Flips the score to the other side's point of view.
+impl Neg for UciScoreValue {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        match self {
+            Self::Cp(cp) => Self::Cp(cp.saturating_neg()),
+            Self::Mate(moves) => Self::Mate(moves.saturating_neg()),
+        }
+    }
+}
+
+impl Display for UciScoreValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cp(cp) => write!(f, "cp {}", cp),
+            Self::Mate(moves) => write!(f, "mate {}", moves),
+        }
+    }
+}
+
+impl UciScore {
+    /// The score value, or `None` if the score has neither or both of
+    /// `cp` and `mate`.
+    pub fn value(&self) -> Option<UciScoreValue> {
+        match (self.cp, self.mate) {
+            (Some(cp), None) => Some(UciScoreValue::Cp(cp)),
+            (None, Some(mate)) => Some(UciScoreValue::Mate(mate)),
+            _ => None,
+        }
+    }
+}
+
+impl UciScore {
+    /// The score from the other side's point of view. WDL is swapped and
+    /// bounds are reversed.
+    pub fn flipped(&self) -> Self {
+        Self {
+            cp: self.cp.map(i32::saturating_neg),
+            mate: self.mate.map(i32::saturating_neg),
+            wdl: self.wdl.map(|(w, d, l)| (l, d, w)),
+            kind: match self.kind {
+                UciScoreKind::Exact => UciScoreKind::Exact,
+                UciScoreKind::LowerBound => UciScoreKind::UpperBound,
+                UciScoreKind::UpperBound => UciScoreKind::LowerBound,
+            },
+        }
+    }
+
+    /// Converts the score from `from`'s point of view to `to`'s.
+    pub fn convert_pov(&self, from: Color, to: Color) -> Self {
+        match from == to {
+            true => *self,
+            false => self.flipped(),
+        }
+    }
+}
+
+impl From<UciScoreValue> for UciScore {
+    fn from(value: UciScoreValue) -> Self {
+        let (cp, mate) = match value {
+            UciScoreValue::Cp(cp) => (Some(cp), None),
+            UciScoreValue::Mate(mate) => (None, Some(mate)),
+        };
+        Self {
+            cp,
+            mate,
+            wdl: None,
+            kind: UciScoreKind::Exact,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct UciCurrline {
     pub cpu: Option<u32>,
@@ -50,6 +165,58 @@ pub struct UciInfo {
     pub currline: Option<UciCurrline>,
 }
 
+impl UciInfo {
+    /// The score from White's point of view, given the searched position.
+    pub fn white_score(&self, board: &Board) -> Option<UciScore> {
+        self.score
+            .map(|score| score.convert_pov(board.side_to_move(), Color::White))
+    }
+
+    /// Converts `score` from `from`'s point of view to `to`'s.
+    pub fn convert_score_pov(&mut self, from: Color, to: Color) {
+        self.score = self.score.map(|score| score.convert_pov(from, to));
+    }
+
+    /// Iterates over the `key=value` tokens in `string`, skipping any other tokens.
+    pub fn string_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.string
+            .as_deref()
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+            .filter_map(|tok| tok.split_once('='))
+    }
+
+    /// Appends a `key=value` token to `string`.
+    pub fn push_string_pair(
+        &mut self,
+        key: &str,
+        value: impl Display,
+    ) -> Result<(), UciFormatError> {
+        use UciFormatErrorKind::*;
+
+        let value = value.to_string();
+        if key.is_empty() {
+            return Err(EmptyToken.in_field("string"));
+        }
+        if key.contains('=') {
+            return Err(EqualsInKey.in_field("string"));
+        }
+        if key.contains(|c: char| c.is_ascii_whitespace())
+            || value.contains(|c: char| c.is_ascii_whitespace())
+        {
+            return Err(WhitespaceInToken.in_field("string"));
+        }
+        let string = self.string.get_or_insert_with(String::new);
+        if !string.is_empty() {
+            string.push(' ');
+        }
+        *string += key;
+        string.push('=');
+        *string += &value;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum UciOptionInfo {
     Check {
@@ -70,12 +237,33 @@ pub enum UciOptionInfo {
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UciBestMove {
+    Move(Move),
+    /// `bestmove (none)`, sent when there are no legal moves.
+    None,
+    /// `bestmove 0000`.
+    Null,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum UciRemark {
     Id(UciIdInfo),
     UciOk,
     ReadyOk,
-    BestMove { mv: Move, ponder: Option<Move> },
+    BestMove {
+        mv: UciBestMove,
+        ponder: Option<Move>,
+    },
     Info(UciInfo),
-    Option { name: String, info: UciOptionInfo },
+    Option {
+        name: String,
+        info: UciOptionInfo,
+    },
+    /// A remark registered with [`UciRegistry`](crate::registry::UciRegistry).
+    Custom(Box<dyn crate::registry::UciExtension>),
+    /// A de facto extension remark. The variant always exists so that the
+    /// `extensions` feature stays additive, but it is only parsed with the
+    /// feature enabled.
+    Ext(crate::extensions::UciExtRemark),
 }
@@ -0,0 +1,197 @@
+use std::fmt::Write;
+
+use cozy_chess::util::{display_san_move, parse_san_move};
+use cozy_chess::{Board, Color, Move};
+
+use crate::command::UciInitPos;
+use crate::moves::{from_uci_move, play_uci_move, to_uci_move};
+use crate::parse::stream::UciTokenStream;
+use crate::remark::{UciBestMove, UciInfo};
+use crate::validate::{UciIllegalMoveError, UciMoveValidator};
+use crate::{UciFormatOptions, UciParseError, UciParseErrorKind, UCI_NULL_MOVE};
+
+const FIGURINES: [(char, char); 5] = [('K', '♔'), ('Q', '♕'), ('R', '♖'), ('B', '♗'), ('N', '♘')];
+
+/// The SAN form of the moves in an info line. Each field is a numbered
+/// line starting from the searched position, like `1. Nf3 d5 2. d4`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct UciSanInfo {
+    pub pv: Option<String>,
+    pub currmove: Option<String>,
+    pub refutation: Option<String>,
+    pub currline: Option<String>,
+}
+
+/// Renders moves from remarks in Standard Algebraic Notation, and parses SAN
+/// back into moves as written in UCI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UciSanFormatter {
+    root: Board,
+    chess960: bool,
+    /// Use figurines such as `♘f3` instead of piece letters.
+    pub figurine: bool,
+}
+
+impl UciSanFormatter {
+    pub fn new(
+        init_pos: &UciInitPos,
+        moves: &[Move],
+        options: &UciFormatOptions,
+    ) -> Result<Self, UciIllegalMoveError> {
+        let root = UciMoveValidator::new(init_pos, moves, options)?
+            .root()
+            .clone();
+        Ok(Self {
+            root,
+            chess960: options.chess960,
+            figurine: false,
+        })
+    }
+
+    pub fn root(&self) -> &Board {
+        &self.root
+    }
+
+    /// Formats a line of moves played from the root, with move numbers.
+    /// The null move is written as `--`.
+    pub fn moves(
+        &self,
+        field: &'static str,
+        moves: &[Move],
+    ) -> Result<String, UciIllegalMoveError> {
+        let mut board = self.root.clone();
+        let mut line = String::new();
+        for (ply, &mv) in moves.iter().enumerate() {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            match board.side_to_move() {
+                Color::White => write!(line, "{}. ", board.fullmove_number()).unwrap(),
+                Color::Black if ply == 0 => {
+                    write!(line, "{}... ", board.fullmove_number()).unwrap()
+                }
+                Color::Black => {}
+            }
+            let san = match mv {
+                UCI_NULL_MOVE => "--".to_owned(),
+                mv => {
+                    let mv = from_uci_move(&board, mv, self.chess960);
+                    match board.is_legal(mv) {
+                        true => display_san_move(&board, mv).to_string(),
+                        false => String::new(),
+                    }
+                }
+            };
+            if san.is_empty() || play_uci_move(&mut board, mv, self.chess960).is_err() {
+                return Err(UciIllegalMoveError {
+                    field,
+                    ply,
+                    mv: Some(mv),
+                    board: Box::new(board),
+                });
+            }
+            line += &self.style(san);
+        }
+        Ok(line)
+    }
+
+    pub fn info(&self, info: &UciInfo) -> Result<UciSanInfo, UciIllegalMoveError> {
+        let line = |field, moves: Option<&[Move]>| moves.map(|m| self.moves(field, m)).transpose();
+        Ok(UciSanInfo {
+            pv: line("pv", info.pv.as_deref())?,
+            currmove: line("currmove", info.currmove.as_ref().map(std::slice::from_ref))?,
+            refutation: line("refutation", info.refutation.as_deref())?,
+            currline: line(
+                "currline",
+                info.currline.as_ref().map(|c| c.moves.as_slice()),
+            )?,
+        })
+    }
+
+    /// Formats a best move and its ponder move as a numbered line.
+    pub fn best_move(
+        &self,
+        mv: UciBestMove,
+        ponder: Option<Move>,
+    ) -> Result<String, UciIllegalMoveError> {
+        match mv {
+            UciBestMove::Move(mv) => {
+                let mut moves = vec![mv];
+                moves.extend(ponder);
+                self.moves("bestmove", &moves)
+            }
+            UciBestMove::None => Ok("(none)".to_owned()),
+            UciBestMove::Null => self.moves("bestmove", &[UCI_NULL_MOVE]),
+        }
+    }
+
+    /// Parses a line of SAN moves played from the root, such as
+    /// `1. Nf3 d5 2. d4`. Move numbers are optional.
+    pub fn parse_moves(&self, s: &str) -> Result<Vec<Move>, UciParseError> {
+        let mut board = self.root.clone();
+        let mut moves = Vec::new();
+        let mut s = UciTokenStream::new(s);
+        while let Ok((tok, span)) = s.read_token() {
+            if is_move_number(tok) {
+                continue;
+            }
+            let mv = self.parse_move(&board, tok, span)?;
+            moves.push(mv);
+            let _ = play_uci_move(&mut board, mv, self.chess960);
+        }
+        Ok(moves)
+    }
+
+    /// Parses SAN moves that are each played from the root, as for
+    /// `go searchmoves`.
+    pub fn parse_searchmoves(&self, s: &str) -> Result<Vec<Move>, UciParseError> {
+        let mut moves = Vec::new();
+        let mut s = UciTokenStream::new(s);
+        while let Ok((tok, span)) = s.read_token() {
+            moves.push(self.parse_move(&self.root, tok, span)?);
+        }
+        Ok(moves)
+    }
+
+    fn parse_move(
+        &self,
+        board: &Board,
+        tok: &str,
+        span: std::ops::Range<usize>,
+    ) -> Result<Move, UciParseError> {
+        if tok == "--" {
+            return match board.null_move() {
+                Some(_) => Ok(UCI_NULL_MOVE),
+                None => Err(UciParseErrorKind::UnexpectedToken(tok.to_owned()).spans(span)),
+            };
+        }
+        let san: String = tok
+            .chars()
+            .map(|c| match FIGURINES.iter().find(|&&(_, f)| f == c) {
+                Some(&(letter, _)) => letter,
+                None => c,
+            })
+            .collect();
+        match parse_san_move(board, &san) {
+            Ok(mv) => Ok(to_uci_move(board, mv, self.chess960)),
+            Err(e) => Err(UciParseErrorKind::from(e).spans(span)),
+        }
+    }
+
+    fn style(&self, san: String) -> String {
+        if !self.figurine {
+            return san;
+        }
+        san.chars()
+            .map(|c| match FIGURINES.iter().find(|&&(l, _)| l == c) {
+                Some(&(_, figurine)) => figurine,
+                None => c,
+            })
+            .collect()
+    }
+}
+
+fn is_move_number(tok: &str) -> bool {
+    let digits = tok.trim_end_matches('.');
+    digits.len() < tok.len() && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
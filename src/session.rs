@@ -0,0 +1,232 @@
+use thiserror::*;
+
+use crate::command::UciCommand;
+use crate::message::UciMessage;
+use crate::remark::{UciInfo, UciRemark};
+use crate::UciFormatOptions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UciSessionState {
+    /// Nothing has been sent yet.
+    #[default]
+    Startup,
+    /// `uci` was sent and the engine is identifying itself.
+    AwaitingUciOk,
+    Idle,
+    Searching,
+    Pondering,
+    Quit,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq, Hash)]
+pub enum UciProtocolViolation {
+    #[error("`{0}` sent before `uci`")]
+    BeforeUci(&'static str),
+    #[error("`{0}` sent before `uciok`")]
+    BeforeUciOk(&'static str),
+    #[error("`{0}` after `quit`")]
+    AfterQuit(&'static str),
+    #[error("`go` sent before any `isready`")]
+    GoBeforeIsReady,
+    #[error("`go` sent before any `position`")]
+    GoWithoutPosition,
+    #[error("`{0}` sent during a search")]
+    DuringSearch(&'static str),
+    #[error("`{0}` sent while not searching")]
+    NotSearching(&'static str),
+    #[error("`ponderhit` sent while not pondering")]
+    NotPondering,
+    #[error("option {0:?} was not declared by the engine")]
+    UnknownOption(String),
+    #[error("`{0}` received outside the `uci` handshake")]
+    OutsideHandshake(&'static str),
+    #[error("`readyok` received without a pending `isready`")]
+    UnsolicitedReadyOk,
+    #[error("`{0}` received while not searching")]
+    RemarkWhileIdle(&'static str),
+    #[error("`bestmove` received before `stop` in an infinite search")]
+    BestMoveBeforeStop,
+    #[error("`bestmove` received while pondering, before `ponderhit` or `stop`")]
+    BestMoveWhilePondering,
+}
+
+/// Tracks the state of a UCI session from the messages sent in both
+/// directions, and reports messages that are out of order.
+///
+/// The state is updated even when a message is a violation, so that a single
+/// mistake is reported once instead of throwing off the rest of the session.
+#[derive(Debug, Clone, Default)]
+pub struct UciSession {
+    state: UciSessionState,
+    options: UciFormatOptions,
+    pending_isready: u32,
+    isready_sent: bool,
+    position_sent: bool,
+    infinite: bool,
+    stop_sent: bool,
+}
+
+impl UciSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> UciSessionState {
+        self.state
+    }
+
+    /// The format options implied by the session so far, for parsing
+    /// further messages.
+    pub fn options(&self) -> &UciFormatOptions {
+        &self.options
+    }
+
+    pub fn update(&mut self, msg: &UciMessage) -> Result<(), UciProtocolViolation> {
+        match msg {
+            UciMessage::Command(cmd) => self.update_command(cmd),
+            UciMessage::Remark(rmk) => self.update_remark(rmk),
+        }
+    }
+
+    /// Updates the session with a command sent by the GUI.
+    pub fn update_command(&mut self, cmd: &UciCommand) -> Result<(), UciProtocolViolation> {
+        use UciProtocolViolation::*;
+        use UciSessionState::*;
+
+        let kind = command_kind(cmd);
+        self.options.update(cmd);
+        match (self.state, cmd) {
+            (Quit, _) => return Err(AfterQuit(kind)),
+            (_, UciCommand::Quit) => self.state = Quit,
+            (Startup, UciCommand::Uci) => self.state = AwaitingUciOk,
+            (Startup, _) => return Err(BeforeUci(kind)),
+            (AwaitingUciOk, _) => return Err(BeforeUciOk(kind)),
+            (_, UciCommand::IsReady) => {
+                self.pending_isready += 1;
+                self.isready_sent = true;
+            }
+            (_, UciCommand::Debug(_) | UciCommand::Custom(_)) => {}
+            // The search may have ended just before the GUI sent `stop`.
+            (Idle, UciCommand::Stop) => {}
+            (Idle, UciCommand::PonderHit) => return Err(NotSearching(kind)),
+            (Searching, UciCommand::PonderHit) => return Err(NotPondering),
+            (Searching | Pondering, UciCommand::Stop) => self.stop_sent = true,
+            (Pondering, UciCommand::PonderHit) => self.state = Searching,
+            (Searching | Pondering, _) => return Err(DuringSearch(kind)),
+            (Idle, UciCommand::SetOption { name, .. }) => {
+                // Option names are case-insensitive.
+                let mut declared = self.options.option_names.iter();
+                if !declared.any(|n| n.eq_ignore_ascii_case(name)) {
+                    return Err(UnknownOption(name.clone()));
+                }
+            }
+            (Idle, UciCommand::Position { .. }) => self.position_sent = true,
+            (Idle, UciCommand::Go(params)) => {
+                self.state = match params.ponder {
+                    true => Pondering,
+                    false => Searching,
+                };
+                self.infinite = params.infinite;
+                self.stop_sent = false;
+                if !self.isready_sent {
+                    return Err(GoBeforeIsReady);
+                }
+                if !self.position_sent {
+                    return Err(GoWithoutPosition);
+                }
+            }
+            (Idle, _) => {}
+        }
+        Ok(())
+    }
+
+    /// Updates the session with a remark sent by the engine.
+    pub fn update_remark(&mut self, rmk: &UciRemark) -> Result<(), UciProtocolViolation> {
+        use UciProtocolViolation::*;
+        use UciSessionState::*;
+
+        let kind = remark_kind(rmk);
+        self.options.update_from_remark(rmk);
+        match (self.state, rmk) {
+            (Quit, _) => return Err(AfterQuit(kind)),
+            (_, UciRemark::Custom(_)) => {}
+            (AwaitingUciOk, UciRemark::Id(_) | UciRemark::Option { .. }) => {}
+            (AwaitingUciOk, UciRemark::UciOk) => self.state = Idle,
+            (_, UciRemark::Id(_) | UciRemark::Option { .. } | UciRemark::UciOk) => {
+                return Err(OutsideHandshake(kind))
+            }
+            (_, UciRemark::ReadyOk) => match self.pending_isready.checked_sub(1) {
+                Some(pending) => self.pending_isready = pending,
+                None => return Err(UnsolicitedReadyOk),
+            },
+            (_, UciRemark::Info(info)) if is_string_only(info) => {}
+            (Searching | Pondering, UciRemark::Info(_)) => {}
+            (Searching | Pondering, UciRemark::BestMove { .. }) => {
+                let state = self.state;
+                self.state = Idle;
+                if !self.stop_sent {
+                    if state == Pondering {
+                        return Err(BestMoveWhilePondering);
+                    }
+                    if self.infinite {
+                        return Err(BestMoveBeforeStop);
+                    }
+                }
+            }
+            (_, UciRemark::Ext(_)) => {}
+            (_, _) => return Err(RemarkWhileIdle(kind)),
+        }
+        Ok(())
+    }
+}
+
+/// `info string` is commonly sent at any time, for example while loading
+/// network files during startup.
+fn is_string_only(info: &UciInfo) -> bool {
+    info.string.is_some()
+        && *info
+            == UciInfo {
+                string: info.string.clone(),
+                ..Default::default()
+            }
+}
+
+fn command_kind(cmd: &UciCommand) -> &'static str {
+    match cmd {
+        UciCommand::Uci => "uci",
+        UciCommand::Debug(_) => "debug",
+        UciCommand::IsReady => "isready",
+        UciCommand::Position { .. } => "position",
+        UciCommand::SetOption { .. } => "setoption",
+        UciCommand::UciNewGame => "ucinewgame",
+        UciCommand::Stop => "stop",
+        UciCommand::PonderHit => "ponderhit",
+        UciCommand::Quit => "quit",
+        UciCommand::Go(_) => "go",
+        UciCommand::Custom(cmd) => cmd.dyn_kind(),
+        UciCommand::Ext(cmd) => {
+            use crate::extensions::UciExtCommand::*;
+            match cmd {
+                D => "d",
+                Eval => "eval",
+                Bench(_) => "bench",
+                Flip => "flip",
+                Compiler => "compiler",
+                ExportNet(_) => "export_net",
+            }
+        }
+    }
+}
+
+fn remark_kind(rmk: &UciRemark) -> &'static str {
+    match rmk {
+        UciRemark::Id(_) => "id",
+        UciRemark::UciOk => "uciok",
+        UciRemark::ReadyOk => "readyok",
+        UciRemark::BestMove { .. } => "bestmove",
+        UciRemark::Info(_) => "info",
+        UciRemark::Option { .. } => "option",
+        UciRemark::Custom(rmk) => rmk.dyn_kind(),
+        UciRemark::Ext(_) => "extension",
+    }
+}
@@ -0,0 +1,647 @@
+//! Scheduling and running tournaments between several engines.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::prelude::*;
+use std::str::FromStr;
+use std::sync::mpsc::channel;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use thiserror::*;
+
+use crate::engine::{UciEngine, UciEngineError};
+use crate::game::{play_game, UciGame, UciGameResult, UciGameSettings, UciTermination};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UciTournamentFormat {
+    /// Every player meets every other player.
+    RoundRobin,
+    /// The first player meets every other player.
+    Gauntlet,
+    /// Players with similar scores meet, without rematches where possible.
+    Swiss { rounds: u32 },
+}
+
+impl FromStr for UciTournamentFormat {
+    type Err = UciTournamentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || UciTournamentError::Format(s.to_owned());
+        match s.split_once(':') {
+            None if s == "round-robin" => Ok(Self::RoundRobin),
+            None if s == "gauntlet" => Ok(Self::Gauntlet),
+            Some(("swiss", rounds)) => Ok(Self::Swiss {
+                rounds: rounds.parse().map_err(|_| error())?,
+            }),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl Display for UciTournamentFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RoundRobin => write!(f, "round-robin"),
+            Self::Gauntlet => write!(f, "gauntlet"),
+            Self::Swiss { rounds } => write!(f, "swiss:{}", rounds),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum UciTournamentError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid tournament format {0:?}")]
+    Format(String),
+    #[error("state file does not match the tournament: {0}")]
+    Mismatch(String),
+    #[error("line {line} of state file: {message}")]
+    State { line: usize, message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UciTournamentConfig {
+    pub players: Vec<String>,
+    pub format: UciTournamentFormat,
+    /// Games played by each pairing, alternating colours.
+    pub games_per_pairing: u32,
+    pub settings: UciGameSettings,
+    /// The most games played at once.
+    pub concurrency: usize,
+}
+
+/// A scheduled game. `seq` numbers the games of the whole tournament in
+/// the order they are scheduled, which is the same on every run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UciPairing {
+    pub seq: usize,
+    pub round: u32,
+    pub white: usize,
+    pub black: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UciGameRecord {
+    pub pairing: UciPairing,
+    pub result: UciGameResult,
+}
+
+/// The finished games and Swiss byes of a tournament.
+///
+/// The state is saved as a text file starting with a header that
+/// describes the tournament, followed by a line per finished game and bye,
+/// so that it can be appended to as games finish:
+///
+/// ```text
+/// format round-robin
+/// games-per-pairing 2
+/// player ./engine-a
+/// player ./engine-b
+/// game 0 1 0 1 1-0
+/// bye 1 2
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct UciTournamentState {
+    pub games: Vec<UciGameRecord>,
+    /// The round and player of each bye, worth a win.
+    pub byes: Vec<(u32, usize)>,
+}
+
+impl UciTournamentState {
+    /// The header of a state file for a tournament.
+    pub fn header(config: &UciTournamentConfig) -> String {
+        let mut header = format!(
+            "format {}\ngames-per-pairing {}\n",
+            config.format, config.games_per_pairing
+        );
+        for player in &config.players {
+            header += &format!("player {}\n", player);
+        }
+        header
+    }
+
+    /// Parses a state file, checking that its header matches `config`.
+    pub fn parse(s: &str, config: &UciTournamentConfig) -> Result<Self, UciTournamentError> {
+        let expected = Self::header(config);
+        let header_lines = expected.lines().count();
+        let lines: Vec<&str> = s.lines().collect();
+        for (i, expected) in expected.lines().enumerate() {
+            match lines.get(i) {
+                Some(line) if line.trim() == expected => {}
+                Some(line) => return Err(UciTournamentError::Mismatch(line.to_string())),
+                None => return Err(UciTournamentError::Mismatch("missing header".to_owned())),
+            }
+        }
+        let players = config.players.len();
+        let mut state = Self::default();
+        for (i, line) in lines.iter().enumerate().skip(header_lines) {
+            let error = |message: &str| UciTournamentError::State {
+                line: i + 1,
+                message: message.to_owned(),
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let player = |s: &str| {
+                s.parse::<usize>()
+                    .ok()
+                    .filter(|&p| p < players)
+                    .ok_or_else(|| error("invalid player"))
+            };
+            match fields[..] {
+                [] => {}
+                ["game", seq, round, white, black, result] => {
+                    let (Ok(seq), Ok(round)) = (seq.parse(), round.parse()) else {
+                        return Err(error("invalid game number"));
+                    };
+                    let result = match result {
+                        "1-0" => UciGameResult::WhiteWins,
+                        "0-1" => UciGameResult::BlackWins,
+                        "1/2-1/2" => UciGameResult::Draw,
+                        _ => return Err(error("invalid result")),
+                    };
+                    let pairing = UciPairing {
+                        seq,
+                        round,
+                        white: player(white)?,
+                        black: player(black)?,
+                    };
+                    state.games.push(UciGameRecord { pairing, result });
+                }
+                ["bye", round, p] => {
+                    let round = round.parse().map_err(|_| error("invalid round"))?;
+                    state.byes.push((round, player(p)?));
+                }
+                _ => return Err(error("unknown line")),
+            }
+        }
+        Ok(state)
+    }
+
+    fn is_finished(&self, seq: usize) -> bool {
+        self.games.iter().any(|g| g.pairing.seq == seq)
+    }
+
+    /// The points of each player, counting byes as wins.
+    pub fn points(&self, players: usize) -> Vec<f64> {
+        let mut points = vec![0.0; players];
+        for game in &self.games {
+            let (white, black) = (game.pairing.white, game.pairing.black);
+            match game.result {
+                UciGameResult::WhiteWins => points[white] += 1.0,
+                UciGameResult::BlackWins => points[black] += 1.0,
+                UciGameResult::Draw => {
+                    points[white] += 0.5;
+                    points[black] += 0.5;
+                }
+            }
+        }
+        for &(_, player) in &self.byes {
+            points[player] += 1.0;
+        }
+        points
+    }
+}
+
+/// Schedules every game of a round-robin or gauntlet tournament. Round-robin
+/// rounds are paired as in Berger tables, so that each player has at most
+/// one pairing per round and alternates colours as far as possible. The
+/// first player of a gauntlet alternates colours from round to round.
+pub fn schedule(players: usize, format: UciTournamentFormat, games: u32) -> Vec<UciPairing> {
+    let mut pairs = Vec::new();
+    match format {
+        UciTournamentFormat::RoundRobin => {
+            // With an odd number of players, the last seat is empty and gives
+            // its opponent a round off.
+            let seats = players + players % 2;
+            let circle = seats - 1;
+            for round in 0..circle {
+                for i in 0..seats / 2 {
+                    // The last seat stays fixed while the others rotate, so
+                    // it changes colour every round, and the other boards
+                    // alternate so that a player moving between them does.
+                    let (a, b) = match i {
+                        0 if round % 2 == 0 => (round, circle),
+                        0 => (circle, round),
+                        _ if i % 2 == 0 => ((round + i) % circle, (round + circle - i) % circle),
+                        _ => ((round + circle - i) % circle, (round + i) % circle),
+                    };
+                    if a < players && b < players {
+                        pairs.push((round as u32 + 1, a, b));
+                    }
+                }
+            }
+        }
+        UciTournamentFormat::Gauntlet => {
+            pairs.extend((1..players).map(|p| match p % 2 {
+                1 => (p as u32, 0, p),
+                _ => (p as u32, p, 0),
+            }));
+        }
+        UciTournamentFormat::Swiss { .. } => {}
+    }
+    let mut schedule = Vec::new();
+    for (round, a, b) in pairs {
+        push_games(&mut schedule, round, a, b, games);
+    }
+    schedule
+}
+
+fn push_games(schedule: &mut Vec<UciPairing>, round: u32, a: usize, b: usize, games: u32) {
+    for game in 0..games {
+        let (white, black) = match game % 2 {
+            0 => (a, b),
+            _ => (b, a),
+        };
+        schedule.push(UciPairing {
+            seq: schedule.len(),
+            round,
+            white,
+            black,
+        });
+    }
+}
+
+/// Pairs a round of a Swiss tournament from the results so far. Players are
+/// ranked by points, and each is paired with the highest ranked player they
+/// have not yet met, backtracking to avoid rematches where possible. If that
+/// takes too long, players are paired in ranking order instead. With
+/// an odd number of players, the lowest ranked player without a bye gets one.
+/// The first of each pair has had fewer games as white.
+pub fn swiss_round(
+    players: usize,
+    state: &UciTournamentState,
+    round: u32,
+) -> (Vec<(usize, usize)>, Option<usize>) {
+    let points = state.points(players);
+    let mut ranking: Vec<usize> = (0..players).collect();
+    ranking.sort_by(|&a, &b| points[b].total_cmp(&points[a]).then(a.cmp(&b)));
+    let mut bye = None;
+    if players % 2 == 1 {
+        let had_bye = |p: usize| state.byes.iter().any(|&(r, b)| b == p && r < round);
+        let p = ranking
+            .iter()
+            .rev()
+            .copied()
+            .find(|&p| !had_bye(p))
+            .unwrap_or(ranking[players - 1]);
+        ranking.retain(|&r| r != p);
+        bye = Some(p);
+    }
+    let met = |a: usize, b: usize| {
+        state.games.iter().any(|g| {
+            let (w, k) = (g.pairing.white, g.pairing.black);
+            (w == a && k == b) || (w == b && k == a)
+        })
+    };
+    let mut steps = SWISS_PAIRING_STEPS;
+    let pairs = pair_unmet(&ranking, &met, &mut steps)
+        .unwrap_or_else(|| ranking.chunks(2).map(|pair| (pair[0], pair[1])).collect());
+    let whites = |p: usize| state.games.iter().filter(|g| g.pairing.white == p).count();
+    let pairs = pairs
+        .into_iter()
+        .map(|(a, b)| match whites(b) < whites(a) {
+            true => (b, a),
+            false => (a, b),
+        })
+        .collect();
+    (pairs, bye)
+}
+
+/// How many partial pairings [`swiss_round`] tries before giving up on
+/// avoiding rematches and pairing players in ranking order.
+const SWISS_PAIRING_STEPS: u32 = 10_000;
+
+fn pair_unmet(
+    ranking: &[usize],
+    met: &impl Fn(usize, usize) -> bool,
+    steps: &mut u32,
+) -> Option<Vec<(usize, usize)>> {
+    let Some((&first, rest)) = ranking.split_first() else {
+        return Some(Vec::new());
+    };
+    if *steps == 0 {
+        return None;
+    }
+    *steps -= 1;
+    for (i, &opponent) in rest.iter().enumerate() {
+        if met(first, opponent) {
+            continue;
+        }
+        let mut remaining = rest.to_vec();
+        remaining.remove(i);
+        if let Some(mut pairs) = pair_unmet(&remaining, met, steps) {
+            pairs.insert(0, (first, opponent));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+/// Runs the games of a tournament that are not yet in `state`, up to
+/// `config.concurrency` at once. Each finished game is added to `state`,
+/// written to `log` as a state file line and passed to `on_game`.
+///
+/// Each concurrent game needs its own engine processes, so `spawn` starts
+/// a player's engine ready to play, with its handshake done and options
+/// set. A player whose engine fails to start loses the game.
+pub fn run_tournament(
+    config: &UciTournamentConfig,
+    state: &mut UciTournamentState,
+    log: &mut impl Write,
+    spawn: &(dyn Fn(usize) -> Result<UciEngine, UciEngineError> + Sync),
+    mut on_game: impl FnMut(&UciGameRecord, &UciGame),
+) -> io::Result<()> {
+    let players = config.players.len();
+    let UciTournamentFormat::Swiss { rounds } = config.format else {
+        let games = schedule(players, config.format, config.games_per_pairing);
+        return run_games(config, state, log, spawn, &mut on_game, games);
+    };
+    let mut seq = 0;
+    for round in 1..=rounds {
+        let (pairs, bye) = swiss_round(players, &without_round(state, round), round);
+        if let Some(player) = bye {
+            if !state.byes.contains(&(round, player)) {
+                writeln!(log, "bye {} {}", round, player)?;
+                log.flush()?;
+                state.byes.push((round, player));
+            }
+        }
+        let mut games = Vec::new();
+        for (white, black) in pairs {
+            push_games(&mut games, round, white, black, config.games_per_pairing);
+        }
+        for game in &mut games {
+            game.seq += seq;
+        }
+        seq += games.len();
+        run_games(config, state, log, spawn, &mut on_game, games)?;
+    }
+    Ok(())
+}
+
+/// The state before a Swiss round, which its pairings are made from.
+fn without_round(state: &UciTournamentState, round: u32) -> UciTournamentState {
+    UciTournamentState {
+        games: state
+            .games
+            .iter()
+            .filter(|g| g.pairing.round < round)
+            .copied()
+            .collect(),
+        byes: state
+            .byes
+            .iter()
+            .filter(|&&(r, _)| r < round)
+            .copied()
+            .collect(),
+    }
+}
+
+fn run_games(
+    config: &UciTournamentConfig,
+    state: &mut UciTournamentState,
+    log: &mut impl Write,
+    spawn: &(dyn Fn(usize) -> Result<UciEngine, UciEngineError> + Sync),
+    on_game: &mut impl FnMut(&UciGameRecord, &UciGame),
+    games: Vec<UciPairing>,
+) -> io::Result<()> {
+    let queue: VecDeque<UciPairing> = games
+        .into_iter()
+        .filter(|g| !state.is_finished(g.seq))
+        .collect();
+    let workers = config.concurrency.clamp(1, queue.len().max(1));
+    let queue = Mutex::new(queue);
+    let (sender, results) = channel();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let queue = &queue;
+            scope.spawn(move || {
+                let mut engines = HashMap::new();
+                loop {
+                    let Some(pairing) = queue.lock().unwrap().pop_front() else {
+                        break;
+                    };
+                    let game = play_pairing(&config.settings, &mut engines, spawn, pairing);
+                    if sender.send((pairing, game)).is_err() {
+                        break;
+                    }
+                }
+                for (_, engine) in engines {
+                    let _ = UciEngine::quit(engine, Duration::from_secs(1));
+                }
+            });
+        }
+        drop(sender);
+        for (pairing, game) in results {
+            let record = UciGameRecord {
+                pairing,
+                result: game.result,
+            };
+            writeln!(
+                log,
+                "game {} {} {} {} {}",
+                pairing.seq, pairing.round, pairing.white, pairing.black, game.result
+            )?;
+            log.flush()?;
+            state.games.push(record);
+            on_game(&record, &game);
+        }
+        Ok(())
+    })
+}
+
+fn play_pairing(
+    settings: &UciGameSettings,
+    engines: &mut HashMap<usize, UciEngine>,
+    spawn: &(dyn Fn(usize) -> Result<UciEngine, UciEngineError> + Sync),
+    pairing: UciPairing,
+) -> UciGame {
+    let mut start = |player: usize| match engines.remove(&player) {
+        Some(engine) => Ok(engine),
+        None => spawn(player),
+    };
+    let forfeit = |result, err: UciEngineError| UciGame {
+        settings: settings.clone(),
+        moves: Vec::new(),
+        result,
+        termination: UciTermination::Disconnected(err.to_string()),
+    };
+    let mut white = match start(pairing.white) {
+        Ok(engine) => engine,
+        Err(err) => return forfeit(UciGameResult::BlackWins, err),
+    };
+    let mut black = match start(pairing.black) {
+        Ok(engine) => engine,
+        Err(err) => {
+            engines.insert(pairing.white, white);
+            return forfeit(UciGameResult::WhiteWins, err);
+        }
+    };
+    let game = play_game(&mut white, &mut black, settings);
+    // An engine that disconnected is started again for its next game.
+    if !matches!(game.termination, UciTermination::Disconnected(_)) {
+        engines.insert(pairing.white, white);
+        engines.insert(pairing.black, black);
+    }
+    game
+}
+
+/// An Elo difference estimated from results, with the half-width of its
+/// 95% confidence interval. Both are infinite when the interval reaches a
+/// perfect or zero score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UciElo {
+    pub elo: f64,
+    pub error: f64,
+}
+
+impl UciElo {
+    pub fn from_results(wins: u32, draws: u32, losses: u32) -> Option<Self> {
+        let games = (wins + draws + losses) as f64;
+        if games == 0.0 {
+            return None;
+        }
+        let (w, d, l) = (
+            wins as f64 / games,
+            draws as f64 / games,
+            losses as f64 / games,
+        );
+        let score = w + d / 2.0;
+        let variance =
+            w * (1.0 - score).powi(2) + d * (0.5 - score).powi(2) + l * (0.0 - score).powi(2);
+        let margin = 1.96 * (variance / games).sqrt();
+        let elo = |p: f64| -400.0 * (1.0 / p - 1.0).log10();
+        Some(Self {
+            elo: elo(score),
+            error: (elo((score + margin).min(1.0)) - elo((score - margin).max(0.0))) / 2.0,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UciCrosstableRow {
+    pub player: usize,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// Points including byes.
+    pub points: f64,
+    /// The player's Elo relative to the opponents they played.
+    pub elo: Option<UciElo>,
+    /// Points and games against each player.
+    pub against: Vec<(f64, u32)>,
+}
+
+/// Standings of a tournament, ranked by points and then Elo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UciCrosstable {
+    pub names: Vec<String>,
+    pub rows: Vec<UciCrosstableRow>,
+}
+
+impl UciCrosstable {
+    pub fn new(names: &[String], state: &UciTournamentState) -> Self {
+        let players = names.len();
+        let points = state.points(players);
+        let mut rows: Vec<UciCrosstableRow> = (0..players)
+            .map(|player| UciCrosstableRow {
+                player,
+                wins: 0,
+                draws: 0,
+                losses: 0,
+                points: points[player],
+                elo: None,
+                against: vec![(0.0, 0); players],
+            })
+            .collect();
+        for game in &state.games {
+            let (white, black) = (game.pairing.white, game.pairing.black);
+            let white_points = match game.result {
+                UciGameResult::WhiteWins => 1.0,
+                UciGameResult::BlackWins => 0.0,
+                UciGameResult::Draw => 0.5,
+            };
+            for (player, opponent, points) in [
+                (white, black, white_points),
+                (black, white, 1.0 - white_points),
+            ] {
+                let row = &mut rows[player];
+                match points {
+                    1.0 => row.wins += 1,
+                    0.0 => row.losses += 1,
+                    _ => row.draws += 1,
+                }
+                row.against[opponent].0 += points;
+                row.against[opponent].1 += 1;
+            }
+        }
+        for row in &mut rows {
+            row.elo = UciElo::from_results(row.wins, row.draws, row.losses);
+        }
+        rows.sort_by(|a, b| {
+            let elo = |row: &UciCrosstableRow| row.elo.map_or(f64::NEG_INFINITY, |e| e.elo);
+            b.points
+                .total_cmp(&a.points)
+                .then(elo(b).total_cmp(&elo(a)))
+                .then(a.player.cmp(&b.player))
+        });
+        Self {
+            names: names.to_vec(),
+            rows,
+        }
+    }
+}
+
+impl Display for UciCrosstable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let width = self.names.iter().map(|n| n.len()).max().unwrap_or(0).max(4);
+        write!(
+            f,
+            "Rank {:width$} {:>6} {:>5} {:>5} {:>6} {:>5}",
+            "Name", "Elo", "+/-", "Games", "Points", "Draws"
+        )?;
+        for rank in 1..=self.rows.len() {
+            write!(f, " {:>7}", rank)?;
+        }
+        writeln!(f)?;
+        for (rank, row) in self.rows.iter().enumerate() {
+            let games = row.wins + row.draws + row.losses;
+            let (elo, error) = match row.elo {
+                Some(e) => (format!("{:.0}", e.elo.round() + 0.0), format_error(e.error)),
+                None => ("-".to_owned(), "-".to_owned()),
+            };
+            write!(
+                f,
+                "{:>4} {:width$} {:>6} {:>5} {:>5} {:>6} {:>5}",
+                rank + 1,
+                self.names[row.player],
+                elo,
+                error,
+                games,
+                row.points,
+                row.draws
+            )?;
+            for other in &self.rows {
+                let cell = match row.against[other.player] {
+                    _ if other.player == row.player => "x".to_owned(),
+                    (_, 0) => "-".to_owned(),
+                    (points, games) => format!("{}/{}", points, games),
+                };
+                write!(f, " {:>7}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn format_error(error: f64) -> String {
+    match error.is_nan() {
+        true => "-".to_owned(),
+        false => format!("{:.0}", error),
+    }
+}
@@ -0,0 +1,544 @@
+//! Presenting a UCI engine to an xboard GUI, as polyglot does.
+
+use std::collections::VecDeque;
+use std::io;
+use std::io::prelude::*;
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+use cozy_chess::{Board, Color, Move};
+
+use crate::cecp::{
+    cecp_feature_option, cecp_score, format_cecp_move, parse_cecp_move, CecpThinking,
+};
+use crate::command::{UciClock, UciCommand, UciGoParams, UciInitPos};
+use crate::moves::play_uci_move;
+use crate::remark::{UciBestMove, UciIdInfo, UciInfo, UciOptionInfo, UciRemark};
+use crate::san::UciSanFormatter;
+use crate::UciFormatOptions;
+
+/// Lines for the xboard GUI and commands for the UCI engine.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Uci2XboardOutput {
+    pub to_engine: Vec<UciCommand>,
+    pub to_gui: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Search {
+    Play,
+    Analyze,
+}
+
+/// The time control set by `level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Level {
+    moves_per_session: u32,
+    base: Duration,
+    inc: Duration,
+}
+
+/// Translates between an xboard GUI speaking CECP and a UCI engine.
+///
+/// Feed it lines from the GUI with [`gui_line`](Self::gui_line) and lines
+/// from the engine with [`engine_line`](Self::engine_line). The engine is
+/// sent `uci` by [`start`](Self::start), and the CECP features are only
+/// completed once it has declared its options.
+#[derive(Debug, Clone)]
+pub struct Uci2Xboard {
+    options: UciFormatOptions,
+    engine_name: Option<String>,
+    engine_options: Vec<(String, UciOptionInfo)>,
+    uciok: bool,
+    features_pending: bool,
+    init_pos: UciInitPos,
+    moves: Vec<Move>,
+    board: Board,
+    force: bool,
+    engine_color: Option<Color>,
+    post: bool,
+    analyzing: bool,
+    search: Option<Search>,
+    stale_bestmoves: u32,
+    pings: VecDeque<String>,
+    level: Level,
+    movetime: Option<Duration>,
+    depth: Option<u32>,
+    own_time: Duration,
+    opponent_time: Duration,
+    quit: bool,
+}
+
+impl Default for Uci2Xboard {
+    fn default() -> Self {
+        let level = Level {
+            moves_per_session: 40,
+            base: Duration::from_secs(300),
+            inc: Duration::ZERO,
+        };
+        Self {
+            options: UciFormatOptions::default(),
+            engine_name: None,
+            engine_options: Vec::new(),
+            uciok: false,
+            features_pending: false,
+            init_pos: UciInitPos::StartPos,
+            moves: Vec::new(),
+            board: Board::default(),
+            force: false,
+            engine_color: Some(Color::Black),
+            post: false,
+            analyzing: false,
+            search: None,
+            stale_bestmoves: 0,
+            pings: VecDeque::new(),
+            level,
+            movetime: None,
+            depth: None,
+            own_time: level.base,
+            opponent_time: level.base,
+            quit: false,
+        }
+    }
+}
+
+impl Uci2Xboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the GUI sent `quit`.
+    pub fn is_done(&self) -> bool {
+        self.quit
+    }
+
+    pub fn start(&mut self) -> Uci2XboardOutput {
+        let mut out = Uci2XboardOutput::default();
+        self.send(&mut out, UciCommand::Uci);
+        out
+    }
+
+    /// Handles a line from the GUI.
+    pub fn gui_line(&mut self, line: &str) -> Uci2XboardOutput {
+        let mut out = Uci2XboardOutput::default();
+        let line = line.trim();
+        let (cmd, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        match cmd {
+            "" | "xboard" | "accepted" | "rejected" | "random" | "computer" | "hard" | "easy"
+            | "name" | "rating" | "ics" | "result" | "." => {}
+            "protover" => match self.uciok {
+                true => self.send_features(&mut out),
+                false => {
+                    self.features_pending = true;
+                    out.to_gui.push("feature done=0".to_owned());
+                }
+            },
+            "new" => {
+                self.stop(&mut out);
+                self.analyzing = false;
+                self.set_position(UciInitPos::StartPos);
+                self.force = false;
+                self.engine_color = Some(Color::Black);
+                self.depth = None;
+                if self.options.chess960 {
+                    self.set_option(&mut out, "UCI_Chess960", Some("false".to_owned()));
+                }
+                self.send(&mut out, UciCommand::UciNewGame);
+            }
+            "variant" => match args {
+                "normal" => {}
+                "fischerandom" => {
+                    self.set_option(&mut out, "UCI_Chess960", Some("true".to_owned()))
+                }
+                _ => out
+                    .to_gui
+                    .push(format!("Error (unsupported variant): {}", args)),
+            },
+            "force" => {
+                self.stop(&mut out);
+                self.force = true;
+                self.engine_color = None;
+            }
+            "go" | "playother" => {
+                self.stop(&mut out);
+                self.force = false;
+                let to_move = self.board.side_to_move();
+                self.engine_color = Some(if cmd == "go" { to_move } else { !to_move });
+                self.think(&mut out);
+            }
+            "usermove" => self.user_move(&mut out, args),
+            "level" => match parse_level(args) {
+                Some(level) => {
+                    self.level = level;
+                    self.movetime = None;
+                }
+                None => out.to_gui.push(format!("Error (bad level): {}", args)),
+            },
+            "st" => match args.parse::<f64>() {
+                Ok(secs) if secs >= 0.0 => self.movetime = Some(Duration::from_secs_f64(secs)),
+                _ => out.to_gui.push(format!("Error (bad time): {}", args)),
+            },
+            "sd" => match args.parse() {
+                Ok(depth) => self.depth = Some(depth),
+                Err(_) => out.to_gui.push(format!("Error (bad depth): {}", args)),
+            },
+            "time" | "otim" => match args.parse::<u64>() {
+                Ok(centis) => {
+                    let time = Duration::from_millis(centis * 10);
+                    match cmd {
+                        "time" => self.own_time = time,
+                        _ => self.opponent_time = time,
+                    }
+                }
+                Err(_) => out.to_gui.push(format!("Error (bad time): {}", args)),
+            },
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "analyze" => {
+                self.stop(&mut out);
+                self.analyzing = true;
+                self.think(&mut out);
+            }
+            "exit" => {
+                self.stop(&mut out);
+                self.analyzing = false;
+            }
+            "?" => {
+                if self.search == Some(Search::Play) {
+                    self.send(&mut out, UciCommand::Stop);
+                }
+            }
+            "ping" => {
+                self.pings.push_back(args.to_owned());
+                self.send(&mut out, UciCommand::IsReady);
+            }
+            "setboard" => match Board::from_fen(args, self.options.chess960) {
+                Ok(board) => {
+                    self.stop(&mut out);
+                    self.set_position(UciInitPos::Board(board));
+                    self.think(&mut out);
+                }
+                Err(_) => out.to_gui.push("tellusererror Illegal position".to_owned()),
+            },
+            "undo" | "remove" => {
+                let plies = if cmd == "undo" { 1 } else { 2 };
+                if self.moves.len() >= plies {
+                    self.stop(&mut out);
+                    let moves = self.moves[..self.moves.len() - plies].to_vec();
+                    self.set_position(self.init_pos.clone());
+                    for mv in moves {
+                        self.play(mv);
+                    }
+                    self.think(&mut out);
+                }
+            }
+            "memory" => self.set_option(&mut out, "Hash", Some(args.to_owned())),
+            "cores" => self.set_option(&mut out, "Threads", Some(args.to_owned())),
+            "option" => {
+                let (name, value) = match args.split_once('=') {
+                    Some((name, value)) => (name.trim(), Some(value.trim())),
+                    None => (args.trim(), None),
+                };
+                let value = match self.engine_option(name) {
+                    Some(UciOptionInfo::Check { .. }) => {
+                        value.map(|v| if v == "1" { "true" } else { "false" })
+                    }
+                    _ => value,
+                };
+                self.set_option(&mut out, name, value.map(str::to_owned));
+            }
+            "quit" => {
+                self.stop(&mut out);
+                self.send(&mut out, UciCommand::Quit);
+                self.quit = true;
+            }
+            // Moves may also be sent without `usermove`.
+            _ if parse_cecp_move(&self.board, cmd, self.options.chess960).is_some() => {
+                self.user_move(&mut out, cmd)
+            }
+            _ => out.to_gui.push(format!("Error (unknown command): {}", cmd)),
+        }
+        out
+    }
+
+    /// Handles a line from the engine.
+    pub fn engine_line(&mut self, line: &str) -> Uci2XboardOutput {
+        let mut out = Uci2XboardOutput::default();
+        let Ok(rmk) = UciRemark::parse_from(line, &self.options) else {
+            return out;
+        };
+        self.options.update_from_remark(&rmk);
+        match rmk {
+            UciRemark::Id(UciIdInfo::Name(name)) => self.engine_name = Some(name),
+            UciRemark::Option { name, info } => self.engine_options.push((name, info)),
+            UciRemark::UciOk => {
+                self.uciok = true;
+                if self.features_pending {
+                    self.send_features(&mut out);
+                }
+            }
+            UciRemark::ReadyOk => {
+                if let Some(ping) = self.pings.pop_front() {
+                    out.to_gui.push(format!("pong {}", ping));
+                }
+            }
+            UciRemark::Info(info) if self.post || self.analyzing => {
+                if let Some(thinking) = self.thinking(&info) {
+                    out.to_gui.push(thinking.to_string());
+                }
+            }
+            UciRemark::BestMove { mv, .. } => {
+                if self.stale_bestmoves > 0 {
+                    self.stale_bestmoves -= 1;
+                    return out;
+                }
+                let search = self.search.take();
+                if let (Some(Search::Play), UciBestMove::Move(mv)) = (search, mv) {
+                    out.to_gui.push(format!(
+                        "move {}",
+                        format_cecp_move(&self.board, mv, self.options.chess960)
+                    ));
+                    self.play(mv);
+                }
+            }
+            _ => {}
+        }
+        out
+    }
+
+    fn send(&mut self, out: &mut Uci2XboardOutput, cmd: UciCommand) {
+        self.options.update(&cmd);
+        out.to_engine.push(cmd);
+    }
+
+    fn engine_option(&self, name: &str) -> Option<&UciOptionInfo> {
+        self.engine_options
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, info)| info)
+    }
+
+    fn set_option(&mut self, out: &mut Uci2XboardOutput, name: &str, value: Option<String>) {
+        if self.engine_option(name).is_none() {
+            out.to_gui.push(format!("Error (unknown option): {}", name));
+            return;
+        }
+        let name = name.to_owned();
+        self.send(out, UciCommand::SetOption { name, value });
+    }
+
+    fn send_features(&mut self, out: &mut Uci2XboardOutput) {
+        self.features_pending = false;
+        let name = self.engine_name.as_deref().unwrap_or("UCI engine");
+        let mut features = format!(
+            "feature myname=\"{}\" setboard=1 usermove=1 ping=1 analyze=1 playother=1 \
+             colors=0 san=0 sigint=0 sigterm=0 reuse=1",
+            name.replace('"', "'")
+        );
+        let variants = match self.engine_option("UCI_Chess960") {
+            Some(_) => "normal,fischerandom",
+            None => "normal",
+        };
+        features += &format!(" variants=\"{}\"", variants);
+        if self.engine_option("Hash").is_some() {
+            features += " memory=1";
+        }
+        if self.engine_option("Threads").is_some() {
+            features += " smp=1";
+        }
+        out.to_gui.push(features);
+        for (name, info) in &self.engine_options {
+            if matches!(name.as_str(), "Hash" | "Threads" | "UCI_Chess960") {
+                continue;
+            }
+            let option = cecp_feature_option(name, info);
+            out.to_gui
+                .push(format!("feature option=\"{}\"", option.replace('"', "'")));
+        }
+        out.to_gui.push("feature done=1".to_owned());
+    }
+
+    fn set_position(&mut self, init_pos: UciInitPos) {
+        self.board = init_pos.clone().into();
+        self.init_pos = init_pos;
+        self.moves.clear();
+    }
+
+    fn play(&mut self, mv: Move) {
+        if play_uci_move(&mut self.board, mv, self.options.chess960).is_ok() {
+            self.moves.push(mv);
+        }
+    }
+
+    fn user_move(&mut self, out: &mut Uci2XboardOutput, text: &str) {
+        let Some(mv) = parse_cecp_move(&self.board, text, self.options.chess960) else {
+            out.to_gui.push(format!("Illegal move: {}", text));
+            return;
+        };
+        self.stop(out);
+        self.play(mv);
+        self.think(out);
+    }
+
+    /// Starts analysing, or searching if it is the engine's turn.
+    fn think(&mut self, out: &mut Uci2XboardOutput) {
+        let search = match self.analyzing {
+            true => Search::Analyze,
+            false if !self.force && self.engine_color == Some(self.board.side_to_move()) => {
+                Search::Play
+            }
+            false => return,
+        };
+        self.send(
+            out,
+            UciCommand::Position {
+                init_pos: self.init_pos.clone(),
+                moves: self.moves.clone(),
+            },
+        );
+        let params = match search {
+            Search::Analyze => UciGoParams {
+                infinite: true,
+                ..Default::default()
+            },
+            Search::Play => self.go_params(),
+        };
+        self.send(out, UciCommand::Go(params));
+        self.search = Some(search);
+    }
+
+    fn go_params(&self) -> UciGoParams {
+        let mut params = UciGoParams {
+            depth: self.depth,
+            ..Default::default()
+        };
+        if let Some(movetime) = self.movetime {
+            params.movetime = Some(movetime);
+            return params;
+        }
+        let (own, opponent) = (
+            Some(UciClock::from(self.own_time)),
+            Some(UciClock::from(self.opponent_time)),
+        );
+        let inc = Some(UciClock::from(self.level.inc));
+        match self.board.side_to_move() {
+            Color::White => (params.wtime, params.btime) = (own, opponent),
+            Color::Black => (params.btime, params.wtime) = (own, opponent),
+        }
+        (params.winc, params.binc) = (inc, inc);
+        let mps = self.level.moves_per_session;
+        if mps > 0 {
+            // The side to move has made every other move since `new` or
+            // `setboard`, whichever side moved first.
+            let played = (self.moves.len() / 2) as u32 % mps;
+            params.movestogo = Some(mps - played);
+        }
+        params
+    }
+
+    /// Stops a search, discarding its `bestmove`.
+    fn stop(&mut self, out: &mut Uci2XboardOutput) {
+        if self.search.take().is_some() {
+            self.send(out, UciCommand::Stop);
+            self.stale_bestmoves += 1;
+        }
+    }
+
+    fn thinking(&self, info: &UciInfo) -> Option<CecpThinking> {
+        let (Some(depth), Some(score), Some(pv)) = (info.depth, info.score, &info.pv) else {
+            return None;
+        };
+        let san = UciSanFormatter::new(&self.init_pos, &self.moves, &self.options)
+            .ok()
+            .and_then(|san| san.moves("pv", pv).ok());
+        let pv = san.unwrap_or_else(|| {
+            pv.iter()
+                .map(|mv| mv.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+        Some(CecpThinking {
+            depth,
+            score: cecp_score(score.value()?),
+            time: info.time.unwrap_or_default(),
+            nodes: info.nodes.unwrap_or(0),
+            pv,
+        })
+    }
+}
+
+/// Parses `MPS BASE INC`, where `BASE` is minutes or `minutes:seconds`.
+fn parse_level(args: &str) -> Option<Level> {
+    let args: Vec<&str> = args.split_whitespace().collect();
+    let [mps, base, inc] = args[..] else {
+        return None;
+    };
+    let base = match base.split_once(':') {
+        Some((mins, secs)) => mins.parse::<u64>().ok()? * 60 + secs.parse::<u64>().ok()?,
+        None => base.parse::<u64>().ok()? * 60,
+    };
+    let inc = inc.parse::<f64>().ok().filter(|inc| *inc >= 0.0)?;
+    Some(Level {
+        moves_per_session: mps.parse().ok()?,
+        base: Duration::from_secs(base),
+        inc: Duration::from_secs_f64(inc),
+    })
+}
+
+/// Runs the adapter until the GUI sends `quit` or either side closes.
+pub fn run_uci2xboard(
+    mut adapter: Uci2Xboard,
+    gui_reader: impl BufRead + Send + 'static,
+    mut gui_writer: impl Write,
+    engine_reader: impl BufRead + Send + 'static,
+    mut engine_writer: impl Write,
+) -> io::Result<()> {
+    enum Line {
+        Gui(String),
+        Engine(String),
+    }
+    let (sender, lines) = channel();
+    let gui_sender = sender.clone();
+    thread::spawn(move || {
+        for line in gui_reader.lines().map_while(Result::ok) {
+            if gui_sender.send(Line::Gui(line)).is_err() {
+                break;
+            }
+        }
+    });
+    thread::spawn(move || {
+        for line in engine_reader.lines().map_while(Result::ok) {
+            if sender.send(Line::Engine(line)).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut out = adapter.start();
+    loop {
+        for cmd in &out.to_engine {
+            match cmd.try_format(&adapter.options) {
+                Ok(line) => writeln!(engine_writer, "{}", line)?,
+                Err(err) => {
+                    out.to_gui
+                        .push(format!("Error ({}): {}", err, cmd.format(&adapter.options)))
+                }
+            }
+        }
+        engine_writer.flush()?;
+        for line in &out.to_gui {
+            writeln!(gui_writer, "{}", line)?;
+        }
+        gui_writer.flush()?;
+        if adapter.is_done() {
+            return Ok(());
+        }
+        out = match lines.recv() {
+            Ok(Line::Gui(line)) => adapter.gui_line(&line),
+            Ok(Line::Engine(line)) => adapter.engine_line(&line),
+            Err(_) => return Ok(()),
+        };
+    }
+}
@@ -0,0 +1,124 @@
+use cozy_chess::{Board, Move};
+use thiserror::*;
+
+use crate::command::UciInitPos;
+use crate::moves::play_uci_move;
+use crate::remark::{UciBestMove, UciInfo, UciRemark};
+use crate::{UciFormatOptions, UCI_NULL_MOVE};
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("{} (position {board})", describe(.field, *.ply, *.mv))]
+pub struct UciIllegalMoveError {
+    pub field: &'static str,
+    pub ply: usize,
+    /// The illegal move, or `None` if the engine sent no move even though
+    /// it had legal moves.
+    pub mv: Option<Move>,
+    pub board: Box<Board>,
+}
+
+/// Checks that the moves in remarks are legal in the position that was
+/// searched, as set up by a `position` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UciMoveValidator {
+    root: Board,
+    chess960: bool,
+}
+
+impl UciMoveValidator {
+    pub fn new(
+        init_pos: &UciInitPos,
+        moves: &[Move],
+        options: &UciFormatOptions,
+    ) -> Result<Self, UciIllegalMoveError> {
+        let mut validator = Self {
+            root: init_pos.clone().into(),
+            chess960: options.chess960,
+        };
+        validator.root = validator.replay("position", moves)?;
+        Ok(validator)
+    }
+
+    pub fn root(&self) -> &Board {
+        &self.root
+    }
+
+    pub fn validate_moves(
+        &self,
+        field: &'static str,
+        moves: &[Move],
+    ) -> Result<(), UciIllegalMoveError> {
+        self.replay(field, moves).map(drop)
+    }
+
+    pub fn validate_info(&self, info: &UciInfo) -> Result<(), UciIllegalMoveError> {
+        if let Some(pv) = &info.pv {
+            self.validate_moves("pv", pv)?;
+        }
+        if let Some(currmove) = info.currmove {
+            self.validate_moves("currmove", &[currmove])?;
+        }
+        if let Some(refutation) = &info.refutation {
+            self.validate_moves("refutation", refutation)?;
+        }
+        if let Some(currline) = &info.currline {
+            self.validate_moves("currline", &currline.moves)?;
+        }
+        Ok(())
+    }
+
+    pub fn validate_remark(&self, remark: &UciRemark) -> Result<(), UciIllegalMoveError> {
+        match remark {
+            UciRemark::Info(info) => self.validate_info(info),
+            UciRemark::BestMove {
+                mv: UciBestMove::Move(mv),
+                ponder,
+            } => {
+                let mut moves = vec![*mv];
+                moves.extend(*ponder);
+                self.validate_moves("bestmove", &moves)
+            }
+            // Engines send no move only when there is no legal move to send.
+            UciRemark::BestMove {
+                mv: UciBestMove::None | UciBestMove::Null,
+                ..
+            } if has_legal_moves(&self.root) => Err(UciIllegalMoveError {
+                field: "bestmove",
+                ply: 0,
+                mv: None,
+                board: Box::new(self.root.clone()),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn replay(&self, field: &'static str, moves: &[Move]) -> Result<Board, UciIllegalMoveError> {
+        let mut board = self.root.clone();
+        for (ply, &mv) in moves.iter().enumerate() {
+            if play_uci_move(&mut board, mv, self.chess960).is_err() {
+                return Err(UciIllegalMoveError {
+                    field,
+                    ply,
+                    mv: Some(mv),
+                    board: Box::new(board),
+                });
+            }
+        }
+        Ok(board)
+    }
+}
+
+fn has_legal_moves(board: &Board) -> bool {
+    board.generate_moves(|mvs| !mvs.is_empty())
+}
+
+fn describe(field: &str, ply: usize, mv: Option<Move>) -> String {
+    match mv {
+        Some(UCI_NULL_MOVE) => format!("illegal move 0000 in {} at ply {}", field, ply),
+        Some(mv) => format!("illegal move {} in {} at ply {}", mv, field, ply),
+        None => format!(
+            "engine returned no move in {} with legal moves available",
+            field
+        ),
+    }
+}
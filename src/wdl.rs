@@ -0,0 +1,363 @@
+use cozy_chess::{Board, Piece};
+
+use crate::remark::{UciScore, UciScoreKind, UciScoreValue};
+
+/// Win, draw and loss probabilities from the side to move's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Wdl {
+    pub win: f64,
+    pub draw: f64,
+    pub loss: f64,
+}
+
+impl Wdl {
+    pub const WIN: Self = Self {
+        win: 1.0,
+        draw: 0.0,
+        loss: 0.0,
+    };
+    pub const LOSS: Self = Self {
+        win: 0.0,
+        draw: 0.0,
+        loss: 1.0,
+    };
+
+    pub fn expected_score(&self) -> f64 {
+        self.win + self.draw / 2.0
+    }
+
+    pub fn from_permill((w, d, l): (u16, u16, u16)) -> Option<Self> {
+        let total = w as f64 + d as f64 + l as f64;
+        if total == 0.0 {
+            return None;
+        }
+        Some(Self {
+            win: w as f64 / total,
+            draw: d as f64 / total,
+            loss: l as f64 / total,
+        })
+    }
+
+    /// The probabilities in permill as sent in `score wdl`, summing to 1000.
+    pub fn to_permill(&self) -> (u16, u16, u16) {
+        let w = (self.win * 1000.0).round().clamp(0.0, 1000.0) as u16;
+        let l = ((self.loss * 1000.0).round().clamp(0.0, 1000.0) as u16).min(1000 - w);
+        (w, 1000 - w - l, l)
+    }
+}
+
+/// The material count used by material-dependent models: pawns count 1,
+/// minor pieces 3, rooks 5 and queens 9, for both sides.
+pub fn material_count(board: &Board) -> u32 {
+    [
+        (Piece::Pawn, 1),
+        (Piece::Knight, 3),
+        (Piece::Bishop, 3),
+        (Piece::Rook, 5),
+        (Piece::Queen, 9),
+    ]
+    .iter()
+    .map(|&(piece, value)| board.pieces(piece).len() * value)
+    .sum()
+}
+
+/// Scores past this many centipawns are treated as decided when inverting a model.
+const CP_LIMIT: f64 = 100_000.0;
+
+/// A model mapping centipawn scores to win, draw and loss probabilities.
+pub trait WdlModel {
+    /// The probabilities for a centipawn score from the side to move's point
+    /// of view, with `material` as given by [`material_count`].
+    fn cp_wdl(&self, cp: f64, material: u32) -> Wdl;
+
+    fn value_wdl(&self, value: UciScoreValue, material: u32) -> Wdl {
+        match value {
+            UciScoreValue::Cp(cp) => self.cp_wdl(cp as f64, material),
+            UciScoreValue::Mate(moves) if moves > 0 => Wdl::WIN,
+            UciScoreValue::Mate(_) => Wdl::LOSS,
+        }
+    }
+
+    /// The probabilities for a score, preferring the engine's own `wdl` if it
+    /// sent one.
+    fn score_wdl(&self, score: &UciScore, board: &Board) -> Option<Wdl> {
+        match score.wdl {
+            Some(wdl) => Wdl::from_permill(wdl),
+            None => Some(self.value_wdl(score.value()?, material_count(board))),
+        }
+    }
+
+    fn expected_score(&self, score: &UciScore, board: &Board) -> Option<f64> {
+        self.score_wdl(score, board).map(|wdl| wdl.expected_score())
+    }
+
+    /// The centipawn score with the given expected score, found by bisection.
+    fn cp_from_expected_score(&self, expected: f64, material: u32) -> f64 {
+        let (mut lo, mut hi) = (-CP_LIMIT, CP_LIMIT);
+        for _ in 0..64 {
+            let mid = (lo + hi) / 2.0;
+            match self.cp_wdl(mid, material).expected_score() < expected {
+                true => lo = mid,
+                false => hi = mid,
+            }
+        }
+        (lo + hi) / 2.0
+    }
+
+    /// A score with both `cp` and `wdl` set from the given probabilities.
+    fn wdl_score(&self, wdl: &Wdl, board: &Board) -> UciScore {
+        let cp = self.cp_from_expected_score(wdl.expected_score(), material_count(board));
+        UciScore {
+            cp: Some(cp.round() as i32),
+            mate: None,
+            wdl: Some(wdl.to_permill()),
+            kind: UciScoreKind::Exact,
+        }
+    }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// The probabilities where winning is a logistic function of `(cp - a) / b`,
+/// and losing the same for the negated score.
+fn two_sided_wdl(cp: f64, a: f64, b: f64) -> Wdl {
+    let win = sigmoid((cp - a) / b);
+    let loss = sigmoid((-cp - a) / b);
+    Wdl {
+        win,
+        draw: (1.0 - win - loss).max(0.0),
+        loss,
+    }
+}
+
+/// A model ignoring material: a score of `a` centipawns is a 50% win chance,
+/// and `b` sets how quickly the chance grows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogisticWdlModel {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Default for LogisticWdlModel {
+    fn default() -> Self {
+        Self { a: 100.0, b: 20.0 }
+    }
+}
+
+impl WdlModel for LogisticWdlModel {
+    fn cp_wdl(&self, cp: f64, _material: u32) -> Wdl {
+        two_sided_wdl(cp, self.a, self.b)
+    }
+}
+
+/// A model in the style of Stockfish's, where `a` and `b` of the logistic
+/// model are cubic polynomials in the material count.
+///
+/// Coefficients are ordered from the cubic term down, and the polynomials are
+/// evaluated at the material count clamped to `17..=78`, divided by 58. The
+/// defaults approximate Stockfish's model for normalized scores.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaterialWdlModel {
+    pub a: [f64; 4],
+    pub b: [f64; 4],
+}
+
+impl MaterialWdlModel {
+    pub const MIN_MATERIAL: u32 = 17;
+    pub const MAX_MATERIAL: u32 = 78;
+    pub const ANCHOR_MATERIAL: u32 = 58;
+
+    pub fn params(&self, material: u32) -> (f64, f64) {
+        let x = material_x(material);
+        (polynomial(&self.a, x), polynomial(&self.b, x))
+    }
+}
+
+impl Default for MaterialWdlModel {
+    fn default() -> Self {
+        Self {
+            a: [0.0, 0.0, 0.0, 100.0],
+            b: [35.291, -65.224, 43.011, 7.5],
+        }
+    }
+}
+
+impl WdlModel for MaterialWdlModel {
+    fn cp_wdl(&self, cp: f64, material: u32) -> Wdl {
+        let (a, b) = self.params(material);
+        two_sided_wdl(cp, a, b)
+    }
+}
+
+fn material_x(material: u32) -> f64 {
+    let material = material.clamp(
+        MaterialWdlModel::MIN_MATERIAL,
+        MaterialWdlModel::MAX_MATERIAL,
+    );
+    material as f64 / MaterialWdlModel::ANCHOR_MATERIAL as f64
+}
+
+fn polynomial(coeffs: &[f64; 4], x: f64) -> f64 {
+    coeffs.iter().fold(0.0, |acc, c| acc * x + c)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WdlOutcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// A position from a finished game: the engine's score and the game's
+/// outcome, both from the side to move's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WdlSample {
+    pub cp: i32,
+    pub material: u32,
+    pub outcome: WdlOutcome,
+}
+
+/// Samples needed in a material bucket for it to be used by [`fit_material`].
+const MIN_BUCKET_SAMPLES: usize = 100;
+
+/// Fits a [`LogisticWdlModel`] by maximum likelihood, or returns `None`
+/// without samples.
+pub fn fit_logistic(samples: &[WdlSample]) -> Option<LogisticWdlModel> {
+    if samples.is_empty() {
+        return None;
+    }
+    let (a, b) = fit_params(samples, LogisticWdlModel::default());
+    Some(LogisticWdlModel { a, b })
+}
+
+/// Fits a [`MaterialWdlModel`] like Stockfish does: a logistic model is fitted
+/// for every material count with enough samples, then cubic polynomials are
+/// fitted through the results. Returns `None` if fewer than four material
+/// counts have enough samples.
+pub fn fit_material(samples: &[WdlSample]) -> Option<MaterialWdlModel> {
+    let start = fit_logistic(samples)?;
+    let mut buckets = vec![Vec::new(); MaterialWdlModel::MAX_MATERIAL as usize + 1];
+    for sample in samples {
+        let material = sample.material.clamp(
+            MaterialWdlModel::MIN_MATERIAL,
+            MaterialWdlModel::MAX_MATERIAL,
+        );
+        buckets[material as usize].push(*sample);
+    }
+    let mut points = Vec::new();
+    for (material, bucket) in buckets.iter().enumerate() {
+        if bucket.len() >= MIN_BUCKET_SAMPLES {
+            let (a, b) = fit_params(bucket, start);
+            let weight = bucket.len() as f64;
+            points.push((material_x(material as u32), a, b, weight));
+        }
+    }
+    if points.len() < 4 {
+        return None;
+    }
+    let a = fit_cubic(points.iter().map(|&(x, a, _, w)| (x, a, w)))?;
+    let b = fit_cubic(points.iter().map(|&(x, _, b, w)| (x, b, w)))?;
+    Some(MaterialWdlModel { a, b })
+}
+
+fn log_likelihood(samples: &[WdlSample], a: f64, b: f64) -> f64 {
+    samples
+        .iter()
+        .map(|sample| {
+            let wdl = two_sided_wdl(sample.cp as f64, a, b);
+            let p = match sample.outcome {
+                WdlOutcome::Win => wdl.win,
+                WdlOutcome::Draw => wdl.draw,
+                WdlOutcome::Loss => wdl.loss,
+            };
+            p.max(1e-12).ln()
+        })
+        .sum()
+}
+
+/// Maximizes the likelihood over `a` and `ln b` with Nelder-Mead.
+fn fit_params(samples: &[WdlSample], start: LogisticWdlModel) -> (f64, f64) {
+    let cost = |[a, ln_b]: [f64; 2]| -log_likelihood(samples, a, ln_b.exp());
+    let start = [start.a, start.b.ln()];
+    let mut simplex = [
+        start,
+        [start[0] + 20.0, start[1]],
+        [start[0], start[1] + 0.5],
+    ]
+    .map(|point| (point, cost(point)));
+    for _ in 0..500 {
+        simplex.sort_by(|(_, x), (_, y)| x.total_cmp(y));
+        let [(best, best_cost), (_, mid_cost), (worst, worst_cost)] = simplex;
+        if worst_cost - best_cost < 1e-6 {
+            break;
+        }
+        let centroid = [
+            (best[0] + simplex[1].0[0]) / 2.0,
+            (best[1] + simplex[1].0[1]) / 2.0,
+        ];
+        let towards = |t: f64| {
+            let point = [
+                centroid[0] + t * (worst[0] - centroid[0]),
+                centroid[1] + t * (worst[1] - centroid[1]),
+            ];
+            (point, cost(point))
+        };
+        let reflected = towards(-1.0);
+        simplex[2] = if reflected.1 < best_cost {
+            let expanded = towards(-2.0);
+            match expanded.1 < reflected.1 {
+                true => expanded,
+                false => reflected,
+            }
+        } else if reflected.1 < mid_cost {
+            reflected
+        } else {
+            let contracted = towards(0.5);
+            if contracted.1 < worst_cost {
+                contracted
+            } else {
+                for vertex in &mut simplex[1..] {
+                    let point = [(best[0] + vertex.0[0]) / 2.0, (best[1] + vertex.0[1]) / 2.0];
+                    *vertex = (point, cost(point));
+                }
+                continue;
+            }
+        };
+    }
+    simplex.sort_by(|(_, x), (_, y)| x.total_cmp(y));
+    let [a, ln_b] = simplex[0].0;
+    (a, ln_b.exp())
+}
+
+/// Weighted least squares fit of a cubic, coefficients from the cubic term down.
+fn fit_cubic(points: impl Iterator<Item = (f64, f64, f64)>) -> Option<[f64; 4]> {
+    let mut system = [[0.0; 5]; 4];
+    for (x, y, w) in points {
+        let powers = [x * x * x, x * x, x, 1.0];
+        for i in 0..4 {
+            for j in 0..4 {
+                system[i][j] += w * powers[i] * powers[j];
+            }
+            system[i][4] += w * powers[i] * y;
+        }
+    }
+    for i in 0..4 {
+        let pivot = (i..4).max_by(|&r, &s| system[r][i].abs().total_cmp(&system[s][i].abs()))?;
+        system.swap(i, pivot);
+        if system[i][i].abs() < 1e-12 {
+            return None;
+        }
+        let pivot = system[i];
+        for (r, row) in system.iter_mut().enumerate() {
+            if r != i {
+                let factor = row[i] / pivot[i];
+                for (x, p) in row.iter_mut().zip(pivot) {
+                    *x -= factor * p;
+                }
+            }
+        }
+    }
+    Some([0, 1, 2, 3].map(|i| system[i][4] / system[i][i]))
+}
@@ -0,0 +1,594 @@
+//! Presenting an xboard engine to a UCI GUI.
+
+use std::collections::VecDeque;
+use std::io;
+use std::io::prelude::*;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use cozy_chess::{Board, Color, Move};
+
+use crate::cecp::{
+    cecp_score_value, format_cecp_move, parse_cecp_feature_option, parse_cecp_features,
+    parse_cecp_move, CecpThinking,
+};
+use crate::command::{UciCommand, UciGoParams, UciInitPos};
+use crate::moves::play_uci_move;
+use crate::remark::{UciBestMove, UciIdInfo, UciInfo, UciOptionInfo, UciRemark};
+use crate::UciFormatOptions;
+
+/// How long an engine has to finish its features before it is assumed to
+/// have none, as xboard does for engines without `feature done`.
+pub const CECP_FEATURE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The `level` sent for searches limited only by nodes or mate, which have
+/// no CECP equivalent: xboard's default of 40 moves in 5 minutes.
+const CECP_DEFAULT_LEVEL: &str = "level 40 5 0";
+
+/// Lines for the xboard engine and remarks for the UCI GUI.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Xboard2UciOutput {
+    pub to_engine: Vec<String>,
+    pub to_gui: Vec<UciRemark>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Search {
+    Play,
+    Analyze,
+    Ponder(Box<UciGoParams>),
+}
+
+/// Translates between a UCI GUI and an engine speaking CECP.
+///
+/// The engine is kept in force mode and only searches on `go`, so the
+/// position from the last `position` command is replayed to it as moves,
+/// or with `new` and `setboard` when it no longer follows from what the
+/// engine has. Infinite searches and pondering are run in analyze mode, and
+/// their `bestmove` is taken from the last PV on `stop`.
+#[derive(Debug, Clone)]
+pub struct Xboard2Uci {
+    options: UciFormatOptions,
+    name: Option<String>,
+    usermove: bool,
+    ping: bool,
+    memory: bool,
+    smp: bool,
+    chess960: bool,
+    engine_options: Vec<(String, UciOptionInfo)>,
+    features_done: bool,
+    features_timeout: bool,
+    uci_pending: bool,
+    pings: VecDeque<u32>,
+    next_ping: u32,
+    init_pos: UciInitPos,
+    moves: Vec<Move>,
+    engine_game: Option<(UciInitPos, Vec<Move>)>,
+    engine_board: Board,
+    level_sent: bool,
+    depth_sent: Option<u32>,
+    movetime_sent: Option<u64>,
+    search: Option<Search>,
+    last_pv: Vec<Move>,
+    quit: bool,
+}
+
+impl Default for Xboard2Uci {
+    fn default() -> Self {
+        Self {
+            options: UciFormatOptions::default(),
+            name: None,
+            usermove: false,
+            ping: false,
+            memory: false,
+            smp: false,
+            chess960: false,
+            engine_options: Vec::new(),
+            features_done: false,
+            features_timeout: true,
+            uci_pending: false,
+            pings: VecDeque::new(),
+            next_ping: 1,
+            init_pos: UciInitPos::StartPos,
+            moves: Vec::new(),
+            engine_game: None,
+            engine_board: Board::default(),
+            level_sent: false,
+            depth_sent: None,
+            movetime_sent: None,
+            search: None,
+            last_pv: Vec::new(),
+            quit: false,
+        }
+    }
+}
+
+impl Xboard2Uci {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the GUI sent `quit`.
+    pub fn is_done(&self) -> bool {
+        self.quit
+    }
+
+    /// Whether the engine has yet to send `feature done=1`.
+    pub fn awaiting_features(&self) -> bool {
+        !self.features_done
+    }
+
+    /// Whether the features should be finished after
+    /// [`CECP_FEATURE_TIMEOUT`], which the engine turns off by sending
+    /// `feature done=0`.
+    pub fn features_time_out(&self) -> bool {
+        !self.features_done && self.features_timeout
+    }
+
+    pub fn start(&mut self) -> Xboard2UciOutput {
+        Xboard2UciOutput {
+            to_engine: vec!["xboard".to_owned(), "protover 2".to_owned()],
+            to_gui: Vec::new(),
+        }
+    }
+
+    /// Stops waiting for features, answering `uci` if the GUI sent it.
+    pub fn finish_features(&mut self) -> Xboard2UciOutput {
+        let mut out = Xboard2UciOutput::default();
+        self.features_done = true;
+        if std::mem::take(&mut self.uci_pending) {
+            self.send_options(&mut out);
+        }
+        out
+    }
+
+    /// Handles a command from the GUI.
+    pub fn command(&mut self, cmd: &UciCommand) -> Xboard2UciOutput {
+        let mut out = Xboard2UciOutput::default();
+        self.options.update(cmd);
+        match cmd {
+            UciCommand::Uci => match self.features_done {
+                true => self.send_options(&mut out),
+                false => self.uci_pending = true,
+            },
+            UciCommand::IsReady => match self.ping {
+                true => {
+                    let ping = self.next_ping;
+                    self.next_ping += 1;
+                    self.pings.push_back(ping);
+                    out.to_engine.push(format!("ping {}", ping));
+                }
+                false => out.to_gui.push(UciRemark::ReadyOk),
+            },
+            UciCommand::Position { init_pos, moves } => {
+                self.init_pos = init_pos.clone();
+                self.moves = moves.clone();
+            }
+            UciCommand::SetOption { name, value } => {
+                let value = value.as_deref();
+                match name.as_str() {
+                    "Hash" if self.memory => out
+                        .to_engine
+                        .push(format!("memory {}", value.unwrap_or(""))),
+                    "Threads" if self.smp => {
+                        out.to_engine.push(format!("cores {}", value.unwrap_or("")))
+                    }
+                    // The variant is sent with `new` on the next search.
+                    "UCI_Chess960" => self.engine_game = None,
+                    _ => match self.engine_option(name) {
+                        Some(UciOptionInfo::Button) => {
+                            out.to_engine.push(format!("option {}", name))
+                        }
+                        Some(UciOptionInfo::Check { .. }) => {
+                            let value = (value == Some("true")) as u8;
+                            out.to_engine.push(format!("option {}={}", name, value))
+                        }
+                        Some(_) => {
+                            out.to_engine
+                                .push(format!("option {}={}", name, value.unwrap_or("")))
+                        }
+                        None => {}
+                    },
+                }
+            }
+            UciCommand::UciNewGame => self.engine_game = None,
+            UciCommand::Go(params) => match params.ponder {
+                true => {
+                    self.sync(&mut out);
+                    self.last_pv.clear();
+                    out.to_engine.push("analyze".to_owned());
+                    self.search = Some(Search::Ponder(Box::new(params.clone())));
+                }
+                false => self.go(&mut out, params),
+            },
+            UciCommand::PonderHit => {
+                if let Some(Search::Ponder(params)) = self.search.clone() {
+                    out.to_engine.push("exit".to_owned());
+                    let params = UciGoParams {
+                        ponder: false,
+                        ..*params
+                    };
+                    self.go(&mut out, &params);
+                }
+            }
+            UciCommand::Stop => match self.search {
+                Some(Search::Play) => out.to_engine.push("?".to_owned()),
+                Some(_) => {
+                    out.to_engine.push("exit".to_owned());
+                    self.search = None;
+                    let mut pv = self.last_pv.iter().copied();
+                    out.to_gui.push(UciRemark::BestMove {
+                        mv: pv.next().map_or(UciBestMove::None, UciBestMove::Move),
+                        ponder: pv.next(),
+                    });
+                }
+                None => {}
+            },
+            UciCommand::Quit => {
+                out.to_engine.push("quit".to_owned());
+                self.quit = true;
+            }
+            _ => {}
+        }
+        out
+    }
+
+    /// Handles a line from the engine.
+    pub fn engine_line(&mut self, line: &str) -> Xboard2UciOutput {
+        let mut out = Xboard2UciOutput::default();
+        let line = line.trim();
+        let (cmd, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        match cmd {
+            "feature" => self.features(&mut out, args),
+            "pong" => {
+                if args.parse().ok() == self.pings.front().copied() {
+                    self.pings.pop_front();
+                    out.to_gui.push(UciRemark::ReadyOk);
+                }
+            }
+            "move" => {
+                let chess960 = self.options.chess960;
+                let Some(mv) = parse_cecp_move(&self.engine_board, args, chess960) else {
+                    out.to_gui.push(info_string(line));
+                    return out;
+                };
+                self.play_engine_move(mv);
+                if self.search == Some(Search::Play) {
+                    self.search = None;
+                    let ponder = match self.last_pv[..] {
+                        [first, ponder, ..] if first == mv => Some(ponder),
+                        _ => None,
+                    };
+                    out.to_gui.push(UciRemark::BestMove {
+                        mv: UciBestMove::Move(mv),
+                        ponder,
+                    });
+                }
+            }
+            "resign" | "1-0" | "0-1" | "1/2-1/2" => {
+                out.to_gui.push(info_string(line));
+                if self.search == Some(Search::Play) {
+                    self.search = None;
+                    out.to_gui.push(UciRemark::BestMove {
+                        mv: UciBestMove::None,
+                        ponder: None,
+                    });
+                }
+            }
+            "Error" | "Illegal" => out.to_gui.push(info_string(line)),
+            "telluser" | "tellusererror" => out.to_gui.push(info_string(args)),
+            _ => {
+                if self.search.is_some() {
+                    if let Some(thinking) = CecpThinking::parse_from(line) {
+                        let info = self.info(&thinking);
+                        out.to_gui.push(UciRemark::Info(info));
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn engine_option(&self, name: &str) -> Option<&UciOptionInfo> {
+        self.engine_options
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, info)| info)
+    }
+
+    fn features(&mut self, out: &mut Xboard2UciOutput, args: &str) {
+        for (name, value) in parse_cecp_features(args) {
+            let accepted = match name.as_str() {
+                "myname" => {
+                    self.name = Some(value.trim().to_owned());
+                    true
+                }
+                "usermove" => {
+                    self.usermove = value == "1";
+                    true
+                }
+                "ping" => {
+                    self.ping = value == "1";
+                    true
+                }
+                "memory" => {
+                    self.memory = value == "1";
+                    true
+                }
+                "smp" => {
+                    self.smp = value == "1";
+                    true
+                }
+                // Moves are always sent in coordinate notation.
+                "san" => value == "0",
+                "variants" => {
+                    self.chess960 = value.split(',').any(|v| v.trim() == "fischerandom");
+                    true
+                }
+                "option" => match parse_cecp_feature_option(&value) {
+                    Some(option) => {
+                        self.engine_options.push(option);
+                        true
+                    }
+                    None => false,
+                },
+                _ => true,
+            };
+            let reply = match accepted {
+                true => "accepted",
+                false => "rejected",
+            };
+            out.to_engine.push(format!("{} {}", reply, name));
+            match (name.as_str(), value.as_str()) {
+                ("done", "1") => out.to_gui.extend(self.finish_features().to_gui),
+                ("done", "0") => self.features_timeout = false,
+                _ => {}
+            }
+        }
+    }
+
+    fn send_options(&mut self, out: &mut Xboard2UciOutput) {
+        let name = self
+            .name
+            .clone()
+            .unwrap_or_else(|| "xboard engine".to_owned());
+        out.to_gui.push(UciRemark::Id(UciIdInfo::Name(name)));
+        let mut options = Vec::new();
+        if self.memory {
+            let info = UciOptionInfo::Spin {
+                default: 16,
+                min: 1,
+                max: 65536,
+            };
+            options.push(("Hash".to_owned(), info));
+        }
+        if self.smp {
+            let info = UciOptionInfo::Spin {
+                default: 1,
+                min: 1,
+                max: 1024,
+            };
+            options.push(("Threads".to_owned(), info));
+        }
+        if self.chess960 {
+            let info = UciOptionInfo::Check { default: false };
+            options.push(("UCI_Chess960".to_owned(), info));
+        }
+        options.extend(self.engine_options.iter().cloned());
+        for (name, info) in options {
+            let rmk = UciRemark::Option { name, info };
+            self.options.update_from_remark(&rmk);
+            out.to_gui.push(rmk);
+        }
+        out.to_gui.push(UciRemark::UciOk);
+    }
+
+    /// Brings the engine to the position from the GUI, in force mode.
+    fn sync(&mut self, out: &mut Xboard2UciOutput) {
+        let follows = match &self.engine_game {
+            Some((init_pos, moves)) => *init_pos == self.init_pos && self.moves.starts_with(moves),
+            None => false,
+        };
+        let played = match follows {
+            true => self
+                .engine_game
+                .as_ref()
+                .map_or(0, |(_, moves)| moves.len()),
+            false => {
+                out.to_engine.push("new".to_owned());
+                if self.options.chess960 {
+                    out.to_engine.push("variant fischerandom".to_owned());
+                }
+                self.level_sent = false;
+                self.depth_sent = None;
+                self.movetime_sent = None;
+                0
+            }
+        };
+        out.to_engine.push("force".to_owned());
+        if !follows {
+            if let UciInitPos::Board(board) = &self.init_pos {
+                let fen = match self.options.chess960 {
+                    false => format!("{}", board),
+                    true => format!("{:#}", board),
+                };
+                out.to_engine.push(format!("setboard {}", fen));
+            }
+            out.to_engine.push("post".to_owned());
+            self.engine_board = self.init_pos.clone().into();
+            self.engine_game = Some((self.init_pos.clone(), Vec::new()));
+        }
+        let moves = self.moves.clone();
+        for &mv in &moves[played..] {
+            let text = format_cecp_move(&self.engine_board, mv, self.options.chess960);
+            out.to_engine.push(match self.usermove {
+                true => format!("usermove {}", text),
+                false => text,
+            });
+            self.play_engine_move(mv);
+        }
+    }
+
+    fn play_engine_move(&mut self, mv: Move) {
+        if play_uci_move(&mut self.engine_board, mv, self.options.chess960).is_ok() {
+            if let Some((_, moves)) = &mut self.engine_game {
+                moves.push(mv);
+            }
+        }
+    }
+
+    fn go(&mut self, out: &mut Xboard2UciOutput, params: &UciGoParams) {
+        let movetime = params
+            .movetime
+            .map(|t| t.as_millis().div_ceil(1000).max(1) as u64);
+        // Only `new` clears the limits set by `sd` and `st`.
+        if (self.depth_sent.is_some() && params.depth.is_none())
+            || (self.movetime_sent.is_some() && movetime.is_none())
+        {
+            self.engine_game = None;
+        }
+        self.sync(out);
+        self.last_pv.clear();
+        let color = self.engine_board.side_to_move();
+        let (time, otim, inc) = match color {
+            Color::White => (params.wtime, params.btime, params.winc),
+            Color::Black => (params.btime, params.wtime, params.binc),
+        };
+        if params.infinite {
+            out.to_engine.push("analyze".to_owned());
+            self.search = Some(Search::Analyze);
+            return;
+        }
+        if let Some(depth) = params.depth.filter(|&d| Some(d) != self.depth_sent) {
+            out.to_engine.push(format!("sd {}", depth));
+            self.depth_sent = Some(depth);
+        }
+        if let Some(secs) = movetime.filter(|&t| Some(t) != self.movetime_sent) {
+            out.to_engine.push(format!("st {}", secs));
+            self.movetime_sent = Some(secs);
+        }
+        if let Some(time) = time {
+            let time = time.to_duration();
+            if !self.level_sent && movetime.is_none() {
+                let secs = time.as_secs().max(1);
+                let inc = inc.map_or(0.0, |inc| inc.to_duration().as_secs_f64());
+                out.to_engine.push(format!(
+                    "level {} {}:{:02} {}",
+                    params.movestogo.unwrap_or(0),
+                    secs / 60,
+                    secs % 60,
+                    inc
+                ));
+                self.level_sent = true;
+            }
+            out.to_engine
+                .push(format!("time {}", time.as_millis() / 10));
+            if let Some(otim) = otim {
+                out.to_engine
+                    .push(format!("otim {}", otim.to_duration().as_millis() / 10));
+            }
+        } else if !self.level_sent && movetime.is_none() && params.depth.is_none() {
+            out.to_engine.push(CECP_DEFAULT_LEVEL.to_owned());
+        }
+        out.to_engine.push("go".to_owned());
+        self.search = Some(Search::Play);
+    }
+
+    fn info(&mut self, thinking: &CecpThinking) -> UciInfo {
+        let chess960 = self.options.chess960;
+        let mut board = self.engine_board.clone();
+        let mut pv = Vec::new();
+        for text in thinking.pv.split_whitespace() {
+            if text.ends_with('.') && text.starts_with(|c: char| c.is_ascii_digit()) {
+                continue;
+            }
+            let Some(mv) = parse_cecp_move(&board, text, chess960) else {
+                break;
+            };
+            let _ = play_uci_move(&mut board, mv, chess960);
+            pv.push(mv);
+        }
+        self.last_pv = pv.clone();
+        UciInfo {
+            depth: Some(thinking.depth),
+            score: Some(cecp_score_value(thinking.score).into()),
+            time: Some(thinking.time),
+            nodes: Some(thinking.nodes),
+            pv: Some(pv),
+            ..Default::default()
+        }
+    }
+}
+
+fn info_string(s: &str) -> UciRemark {
+    UciRemark::Info(UciInfo {
+        string: Some(s.trim().to_owned()),
+        ..Default::default()
+    })
+}
+
+/// Runs the adapter until the GUI sends `quit` or either side closes. If the
+/// engine does not finish its features within [`CECP_FEATURE_TIMEOUT`] of its
+/// last line, it is assumed to have none, unless it sent `feature done=0`.
+pub fn run_xboard2uci(
+    mut adapter: Xboard2Uci,
+    gui_reader: impl BufRead + Send + 'static,
+    mut gui_writer: impl Write,
+    engine_reader: impl BufRead + Send + 'static,
+    mut engine_writer: impl Write,
+) -> io::Result<()> {
+    enum Line {
+        Gui(String),
+        Engine(String),
+    }
+    let (sender, lines) = channel();
+    let gui_sender = sender.clone();
+    thread::spawn(move || {
+        for line in gui_reader.lines().map_while(Result::ok) {
+            if gui_sender.send(Line::Gui(line)).is_err() {
+                break;
+            }
+        }
+    });
+    thread::spawn(move || {
+        for line in engine_reader.lines().map_while(Result::ok) {
+            if sender.send(Line::Engine(line)).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut out = adapter.start();
+    loop {
+        for line in &out.to_engine {
+            writeln!(engine_writer, "{}", line)?;
+        }
+        engine_writer.flush()?;
+        for rmk in &out.to_gui {
+            // Remarks built from engine output may not be representable.
+            let line = rmk
+                .try_format(&adapter.options)
+                .unwrap_or_else(|err| info_string(&err.to_string()).format(&adapter.options));
+            writeln!(gui_writer, "{}", line)?;
+        }
+        gui_writer.flush()?;
+        if adapter.is_done() {
+            return Ok(());
+        }
+        let line = match adapter.features_time_out() {
+            true => lines.recv_timeout(CECP_FEATURE_TIMEOUT),
+            false => lines.recv().map_err(RecvTimeoutError::from),
+        };
+        out = match line {
+            Ok(Line::Gui(line)) => match UciCommand::parse_from(&line, &adapter.options) {
+                Ok(cmd) => adapter.command(&cmd),
+                Err(_) => Xboard2UciOutput::default(),
+            },
+            Ok(Line::Engine(line)) => adapter.engine_line(&line),
+            Err(RecvTimeoutError::Timeout) => adapter.finish_features(),
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+    }
+}
@@ -0,0 +1,194 @@
+use std::io::prelude::*;
+use std::io::{pipe, BufReader};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use cozy_uci::adapter::*;
+
+const CONFIG: &str = "
+# Engine setup
+engine ./mock
+arg --quiet
+set UCI_ShowWDL = true
+set Clear Hash
+override Hash = 64
+drop Debug Log File
+hide EvalFile
+name Mock (adapted)
+clamp movetime 50 1000
+clamp wtime 0 60000
+";
+
+fn adapter() -> UciAdapter {
+    UciAdapter::new(UciAdapterConfig::parse(CONFIG).unwrap())
+}
+
+fn to_engine(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|&l| l.to_owned()).collect()
+}
+
+#[test]
+fn adapter_config() {
+    let config = UciAdapterConfig::parse(CONFIG).unwrap();
+    assert_eq!(config.engine.as_deref(), Some("./mock"));
+    assert_eq!(config.args, ["--quiet"]);
+    assert_eq!(
+        config.set,
+        [
+            ("UCI_ShowWDL".to_owned(), Some("true".to_owned())),
+            ("Clear Hash".to_owned(), None)
+        ]
+    );
+    assert_eq!(config.drop, ["Debug Log File"]);
+    assert_eq!(
+        config.clamp,
+        [
+            (UciClockField::Movetime, 50, 1000),
+            (UciClockField::Wtime, 0, 60000)
+        ]
+    );
+
+    let err = UciAdapterConfig::parse("name X\nclamp movetime 10\n").unwrap_err();
+    assert_eq!(err.line, 2);
+    let err = UciAdapterConfig::parse("frobnicate 3").unwrap_err();
+    assert_eq!(err.to_string(), "line 1: unknown directive");
+    assert!(UciAdapterConfig::parse("clamp movetime 100 10").is_err());
+    assert!(UciAdapterConfig::parse("drop").is_err());
+}
+
+#[test]
+fn adapter_handshake() {
+    let mut adapter = adapter();
+    assert_eq!(adapter.command("uci").to_engine, ["uci"]);
+    assert_eq!(
+        adapter.remark("id name Mock 1.0").to_gui,
+        ["id name Mock (adapted)"]
+    );
+    assert_eq!(
+        adapter.remark("id author Someone").to_gui,
+        ["id author Someone"]
+    );
+    assert_eq!(
+        adapter.remark("option name EvalFile type string default nn.nnue"),
+        UciAdapterOutput::default()
+    );
+    let hash = "option name Hash type spin default 16 min 1 max 1024";
+    assert_eq!(adapter.remark(hash).to_gui, [hash]);
+    assert_eq!(
+        adapter.remark("uciok"),
+        UciAdapterOutput {
+            to_engine: to_engine(&[
+                "setoption name UCI_ShowWDL value true",
+                "setoption name Clear Hash"
+            ]),
+            to_gui: to_engine(&["uciok"]),
+        }
+    );
+    assert_eq!(
+        adapter.command("setoption name Hash value 4096").to_engine,
+        ["setoption name Hash value 64"]
+    );
+    assert_eq!(
+        adapter.command("setoption name Debug Log File value x.log"),
+        UciAdapterOutput::default()
+    );
+    assert_eq!(
+        adapter.command("mystery command").to_engine,
+        ["mystery command"]
+    );
+    assert_eq!(
+        adapter.remark("Mock 1.0 by someone").to_gui,
+        ["Mock 1.0 by someone"]
+    );
+}
+
+#[test]
+fn adapter_rewrites_search() {
+    let mut adapter = adapter();
+    adapter.remark("uciok");
+    assert_eq!(
+        adapter
+            .command("go wtime 100000 btime 100000 movetime 5")
+            .to_engine,
+        ["go wtime 60000 btime 100000 movetime 50"]
+    );
+    // The engine was told to send WDL, but the GUI never asked for it.
+    assert_eq!(
+        adapter
+            .remark("info depth 3 score cp 20 wdl 100 850 50 pv e2e4")
+            .to_gui,
+        ["info depth 3 pv e2e4 score cp 20"]
+    );
+    adapter.command("setoption name UCI_ShowWDL value true");
+    assert_eq!(
+        adapter
+            .remark("info depth 3 score cp 20 wdl 100 850 50 pv e2e4")
+            .to_gui,
+        ["info depth 3 pv e2e4 score cp 20 wdl 100 850 50"]
+    );
+}
+
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn adapter_runs_between_pipes() {
+    let (cmd_reader, cmd_writer) = pipe().unwrap();
+    let (rmk_reader, mut rmk_writer) = pipe().unwrap();
+    let received = SharedBuf::default();
+    let engine = {
+        let mut received = received.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(cmd_reader).lines() {
+                let line = line.unwrap();
+                writeln!(received, "{}", line).unwrap();
+                match line.as_str() {
+                    "uci" => writeln!(rmk_writer, "id name Mock\nuciok").unwrap(),
+                    "quit" => break,
+                    _ => {}
+                }
+            }
+        })
+    };
+    let (gui_reader, mut gui_writer) = pipe().unwrap();
+    let gui_output = SharedBuf::default();
+    let gui = {
+        let gui_output = gui_output.clone();
+        thread::spawn(move || {
+            writeln!(gui_writer, "uci").unwrap();
+            while !gui_output.0.lock().unwrap().ends_with(b"uciok\n") {
+                thread::yield_now();
+            }
+            writeln!(gui_writer, "setoption name Hash value 1\nquit").unwrap();
+        })
+    };
+    run_adapter(
+        adapter(),
+        BufReader::new(gui_reader),
+        gui_output.clone(),
+        BufReader::new(rmk_reader),
+        cmd_writer,
+    )
+    .unwrap();
+    engine.join().unwrap();
+    gui.join().unwrap();
+
+    let gui_output = String::from_utf8(gui_output.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(gui_output, "id name Mock (adapted)\nuciok\n");
+    let received = String::from_utf8(received.0.lock().unwrap().clone()).unwrap();
+    let received: Vec<&str> = received.lines().collect();
+    assert_eq!(received[0], "uci");
+    assert!(received.contains(&"setoption name Hash value 64"));
+    assert!(received.contains(&"setoption name UCI_ShowWDL value true"));
+    assert_eq!(received.last(), Some(&"quit"));
+}
@@ -0,0 +1,57 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::io::{pipe, BufReader, PipeReader, PipeWriter};
+use std::thread::{self, JoinHandle};
+
+use cozy_uci::command::UciCommand;
+use cozy_uci::engine::UciEngine;
+use cozy_uci::message::UciMessage;
+use cozy_uci::remark::UciRemark;
+use cozy_uci::UciFormatOptions;
+
+/// Replays a log of `>` commands and `<` remarks, skipping `#` comments,
+/// and passes each message to `visit` with the options it was parsed with.
+/// `setoption` commands update the options for later lines.
+pub fn replay_log(log_path: &str, mut visit: impl FnMut(UciMessage, &UciFormatOptions)) {
+    let log = std::fs::read_to_string(log_path).expect("failed to find log file");
+    let mut options = UciFormatOptions::default();
+    for line in log.lines() {
+        match line.split_at(1) {
+            // Comments, such as the timestamps written by `uci-proxy`.
+            ("#", _) => {}
+            (">", cmd_str) => {
+                let cmd =
+                    UciCommand::parse_from(cmd_str, &options).expect("failed to parse command");
+                visit(cmd.clone().into(), &options);
+                options.update(&cmd);
+            }
+            ("<", rmk_str) => {
+                let rmk = UciRemark::parse_from(rmk_str, &options).expect("failed to parse remark");
+                visit(rmk.into(), &options);
+            }
+            _ => panic!("incorrectly formatted line in log file"),
+        }
+    }
+}
+
+/// Runs a mock engine on a thread, reading commands and writing remarks
+/// through pipes. Returns the ends for the GUI: the remark reader and the
+/// command writer.
+pub fn spawn_mock(
+    mock: impl FnOnce(BufReader<PipeReader>, PipeWriter) + Send + 'static,
+) -> (BufReader<PipeReader>, PipeWriter, JoinHandle<()>) {
+    let (cmd_reader, cmd_writer) = pipe().unwrap();
+    let (rmk_reader, rmk_writer) = pipe().unwrap();
+    let handle = thread::spawn(move || mock(BufReader::new(cmd_reader), rmk_writer));
+    (BufReader::new(rmk_reader), cmd_writer, handle)
+}
+
+/// Runs a mock engine as in [`spawn_mock`], connected to a [`UciEngine`].
+pub fn spawn_mock_engine(
+    mock: impl FnOnce(BufReader<PipeReader>, PipeWriter) + Send + 'static,
+) -> (UciEngine, JoinHandle<()>) {
+    let (reader, writer, handle) = spawn_mock(mock);
+    (UciEngine::from_io(reader, writer), handle)
+}
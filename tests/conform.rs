@@ -0,0 +1,134 @@
+mod common;
+
+use std::io::prelude::*;
+use std::time::Duration;
+
+use cozy_chess::*;
+use cozy_uci::command::{UciCommand, UciGoParams};
+use cozy_uci::conform::{check_conformance, UciConformConfig};
+use cozy_uci::UciFormatOptions;
+
+#[derive(Default, Clone, Copy)]
+struct Quirks {
+    returns_from_infinite: bool,
+    ignores_isready_while_searching: bool,
+    garbage_after_uciok: bool,
+}
+
+fn mock_engine(input: impl BufRead, mut output: impl Write, quirks: Quirks) {
+    let options = UciFormatOptions::default();
+    let mut board = Board::default();
+    let mut searching = false;
+    macro_rules! send {
+        ($($arg:tt)*) => {
+            writeln!(output, $($arg)*).unwrap()
+        };
+    }
+    let best_move = |board: &Board| {
+        let mut best = None;
+        board.generate_moves(|mvs| {
+            best = mvs.into_iter().next();
+            true
+        });
+        best.unwrap()
+    };
+    for line in input.lines() {
+        match UciCommand::parse_from(&line.unwrap(), &options).unwrap() {
+            UciCommand::Uci => {
+                send!("id name Mock");
+                send!("id author cozy-uci");
+                send!("option name Hash type spin default 16 min 1 max 1024");
+                send!("uciok");
+                if quirks.garbage_after_uciok {
+                    send!("Mock 1.0 by cozy-uci");
+                }
+            }
+            UciCommand::IsReady if !(searching && quirks.ignores_isready_while_searching) => {
+                send!("readyok");
+            }
+            UciCommand::Position { init_pos, moves } => {
+                board = init_pos.into();
+                for mv in moves {
+                    board.play(mv);
+                }
+            }
+            UciCommand::Go(UciGoParams {
+                infinite, ponder, ..
+            }) => {
+                send!("info depth 1 score cp 0 pv {}", best_move(&board));
+                searching = ponder || infinite && !quirks.returns_from_infinite;
+                if !searching {
+                    send!("bestmove {}", best_move(&board));
+                }
+            }
+            UciCommand::Stop | UciCommand::PonderHit if searching => {
+                searching = false;
+                send!("bestmove {}", best_move(&board));
+            }
+            UciCommand::Quit => break,
+            _ => {}
+        }
+    }
+}
+
+fn run_checks(quirks: Quirks) -> Vec<(&'static str, bool)> {
+    let (mut engine, mock) =
+        common::spawn_mock_engine(move |input, output| mock_engine(input, output, quirks));
+    let config = UciConformConfig {
+        timeout: Duration::from_millis(500),
+        search_time: Duration::from_millis(50),
+    };
+    let checks = check_conformance(&mut engine, &config);
+    engine.quit(config.timeout).unwrap();
+    mock.join().unwrap();
+    checks
+        .into_iter()
+        .map(|check| (check.name, check.result.is_ok()))
+        .collect()
+}
+
+fn failed_checks(quirks: Quirks) -> Vec<&'static str> {
+    run_checks(quirks)
+        .into_iter()
+        .filter(|(_, ok)| !ok)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+#[test]
+fn conforming_engine_passes() {
+    let checks = run_checks(Quirks::default());
+    assert_eq!(checks.len(), 8);
+    assert!(checks.iter().all(|(_, ok)| *ok), "{:?}", checks);
+}
+
+#[test]
+fn infinite_search_returning_fails() {
+    let failed = failed_checks(Quirks {
+        returns_from_infinite: true,
+        ..Default::default()
+    });
+    assert!(failed.contains(&"go infinite does not return"));
+    // The search needed to check isready has already ended.
+    assert!(failed.contains(&"isready answered during search"));
+    assert!(!failed.contains(&"stop yields exactly one bestmove"));
+}
+
+#[test]
+fn ignored_isready_fails() {
+    let failed = failed_checks(Quirks {
+        ignores_isready_while_searching: true,
+        ..Default::default()
+    });
+    assert!(failed.contains(&"isready answered during search"));
+    assert_eq!(failed, ["isready answered during search"]);
+}
+
+#[test]
+fn unparsable_remark_fails() {
+    let failed = failed_checks(Quirks {
+        garbage_after_uciok: true,
+        ..Default::default()
+    });
+    assert_eq!(failed, ["every remark parses"]);
+}
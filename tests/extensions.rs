@@ -0,0 +1,198 @@
+use std::time::Duration;
+
+use cozy_chess::{Board, Square};
+use cozy_uci::command::UciCommand;
+use cozy_uci::extensions::*;
+use cozy_uci::message::UciMessage;
+use cozy_uci::remark::UciRemark;
+use cozy_uci::{UciFormatErrorKind, UciFormatOptions, UciParseErrorKind};
+
+fn roundtrip(msg: &UciMessage, options: &UciFormatOptions) {
+    let formatted = msg.format(options);
+    let reparsed = UciMessage::parse_from(&formatted, options).expect("failed to reparse");
+    assert_eq!(&reparsed, msg, "roundtripped message is not identical");
+}
+
+#[test]
+fn ext_commands() {
+    let options = UciFormatOptions::default();
+    let cases = [
+        ("d", UciExtCommand::D),
+        ("eval", UciExtCommand::Eval),
+        ("flip", UciExtCommand::Flip),
+        ("compiler", UciExtCommand::Compiler),
+        ("export_net", UciExtCommand::ExportNet(None)),
+        (
+            "export_net nets/my net.nnue",
+            UciExtCommand::ExportNet(Some("nets/my net.nnue".to_owned())),
+        ),
+        ("bench", UciExtCommand::Bench(UciBenchParams::default())),
+        (
+            "bench 64 4",
+            UciExtCommand::Bench(UciBenchParams {
+                hash: Some(64),
+                threads: Some(4),
+                ..Default::default()
+            }),
+        ),
+        (
+            "bench 16 1 5000 current nodes",
+            UciExtCommand::Bench(UciBenchParams {
+                hash: Some(16),
+                threads: Some(1),
+                limit: Some(5000),
+                fen_file: Some("current".to_owned()),
+                limit_type: Some(UciBenchLimitType::Nodes),
+            }),
+        ),
+    ];
+    for (line, ext) in cases {
+        let cmd = UciCommand::parse_from(line, &options).unwrap();
+        assert_eq!(cmd, UciCommand::Ext(ext));
+        assert_eq!(cmd.format(&options), line);
+        roundtrip(&cmd.into(), &options);
+    }
+
+    let err = UciCommand::parse_from("bench 16 1 13 default fast", &options).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::UnexpectedToken(tok) if tok == "fast"));
+    let err = UciCommand::parse_from("d now", &options).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::UnexpectedToken(tok) if tok == "now"));
+}
+
+#[test]
+fn ext_command_format_errors() {
+    let options = UciFormatOptions::default();
+    let skipped = UciCommand::Ext(UciExtCommand::Bench(UciBenchParams {
+        hash: Some(16),
+        limit: Some(13),
+        ..Default::default()
+    }));
+    let err = skipped.try_format(&options).unwrap_err();
+    assert_eq!(err.field, "limit");
+    assert_eq!(err.kind, UciFormatErrorKind::SkippedArgument("threads"));
+
+    let spaced = UciCommand::Ext(UciExtCommand::Bench(UciBenchParams {
+        hash: Some(16),
+        threads: Some(1),
+        limit: Some(13),
+        fen_file: Some("my fens.epd".to_owned()),
+        limit_type: None,
+    }));
+    let err = spaced.try_format(&options).unwrap_err();
+    assert_eq!(err.kind, UciFormatErrorKind::WhitespaceInToken);
+
+    let empty = UciCommand::Ext(UciExtCommand::ExportNet(Some(String::new())));
+    assert_eq!(
+        empty.try_format(&options).unwrap_err().kind,
+        UciFormatErrorKind::EmptyToken
+    );
+}
+
+#[test]
+fn ext_remarks() {
+    let options = UciFormatOptions::default();
+    let parse = |line: &str| match UciRemark::parse_from(line, &options) {
+        Ok(UciRemark::Ext(ext)) => ext,
+        other => panic!("unexpected parse of {:?}: {:?}", line, other),
+    };
+
+    let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+    assert_eq!(
+        parse(&format!("Fen: {}", fen)),
+        UciExtRemark::Fen(fen.parse::<Board>().unwrap())
+    );
+    assert_eq!(
+        parse("Key: 8F8F01D4562F59FB"),
+        UciExtRemark::Key(0x8F8F01D4562F59FB)
+    );
+    assert_eq!(parse("Checkers: "), UciExtRemark::Checkers(vec![]));
+    assert_eq!(
+        parse("Checkers: e2 h4"),
+        UciExtRemark::Checkers(vec![Square::E2, Square::H4])
+    );
+    assert_eq!(
+        parse("Final evaluation       +0.25 (white side) [with scaled NNUE, ...]"),
+        UciExtRemark::FinalEvaluation {
+            cp: Some(25),
+            note: "(white side) [with scaled NNUE, ...]".to_owned(),
+        }
+    );
+    assert_eq!(
+        parse("Final evaluation: -1.3 (white side)"),
+        UciExtRemark::FinalEvaluation {
+            cp: Some(-130),
+            note: "(white side)".to_owned(),
+        }
+    );
+    assert_eq!(
+        parse("Final evaluation: none (in check)"),
+        UciExtRemark::FinalEvaluation {
+            cp: None,
+            note: "(in check)".to_owned(),
+        }
+    );
+    assert_eq!(
+        parse("Total time (ms) : 2087"),
+        UciExtRemark::TotalTime(Duration::from_millis(2087))
+    );
+    assert_eq!(
+        parse("Nodes searched  : 2351485"),
+        UciExtRemark::NodesSearched(2351485)
+    );
+    assert_eq!(
+        parse("Nodes/second    : 1126729"),
+        UciExtRemark::NodesPerSecond(1126729)
+    );
+
+    let err = UciRemark::parse_from("Final evaluation +0.255", &options).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        UciParseErrorKind::InvalidField("evaluation")
+    ));
+    let err = UciRemark::parse_from("Checkers: e9", &options).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        UciParseErrorKind::InvalidField("Checkers")
+    ));
+}
+
+#[test]
+fn ext_remark_roundtrip() {
+    let options = UciFormatOptions::default();
+    let remarks = [
+        UciExtRemark::Fen(Board::default()),
+        UciExtRemark::Key(0x1F),
+        UciExtRemark::Checkers(vec![Square::A1]),
+        UciExtRemark::FinalEvaluation {
+            cp: Some(-5),
+            note: "(white side)".to_owned(),
+        },
+        UciExtRemark::FinalEvaluation {
+            cp: Some(i32::MIN),
+            note: String::new(),
+        },
+        UciExtRemark::TotalTime(Duration::from_millis(13)),
+        UciExtRemark::NodesSearched(0),
+        UciExtRemark::NodesPerSecond(u64::MAX),
+    ];
+    for remark in remarks {
+        roundtrip(&UciRemark::Ext(remark).into(), &options);
+    }
+    assert_eq!(
+        UciRemark::Ext(UciExtRemark::Key(0x1F)).format(&options),
+        "Key: 000000000000001F"
+    );
+}
+
+#[test]
+fn ext_message_direction() {
+    let options = UciFormatOptions::default();
+    assert!(matches!(
+        UciMessage::parse_from("bench", &options),
+        Ok(UciMessage::Command(UciCommand::Ext(_)))
+    ));
+    assert!(matches!(
+        UciMessage::parse_from("Nodes searched  : 12", &options),
+        Ok(UciMessage::Remark(UciRemark::Ext(_)))
+    ));
+}
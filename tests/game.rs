@@ -0,0 +1,237 @@
+use std::io::prelude::*;
+use std::io::{pipe, BufReader};
+use std::thread;
+use std::time::Duration;
+
+use cozy_chess::{Board, Color};
+use cozy_uci::command::{UciCommand, UciInitPos};
+use cozy_uci::engine::UciEngine;
+use cozy_uci::game::*;
+use cozy_uci::remark::UciScoreValue;
+use cozy_uci::UciFormatOptions;
+
+enum Reply {
+    Move(&'static str),
+    Silent,
+    Disconnect,
+}
+
+/// An engine that answers `go` with the reply for the number of moves
+/// played so far.
+fn mock(reply: impl Fn(usize) -> Reply + Send + 'static) -> UciEngine {
+    let (input, writer) = pipe().unwrap();
+    let (reader, mut output) = pipe().unwrap();
+    thread::spawn(move || {
+        let mut plies = 0;
+        for line in BufReader::new(input).lines() {
+            let line = line.unwrap();
+            let cmd = UciCommand::parse_from(&line, &UciFormatOptions::default()).unwrap();
+            let response = match cmd {
+                UciCommand::Uci => "id name Mock\nuciok\n".to_owned(),
+                UciCommand::IsReady => "readyok\n".to_owned(),
+                UciCommand::Position { moves, .. } => {
+                    plies = moves.len();
+                    continue;
+                }
+                UciCommand::Go(_) => match reply(plies) {
+                    Reply::Move(mv) => format!("info depth 3 score cp 25\nbestmove {}\n", mv),
+                    Reply::Silent => continue,
+                    Reply::Disconnect => return,
+                },
+                _ => continue,
+            };
+            if output.write_all(response.as_bytes()).is_err() {
+                return;
+            }
+        }
+    });
+    UciEngine::from_io(BufReader::new(reader), writer)
+}
+
+/// Plays the moves of a line in turn, each engine picking its own plies.
+fn line(moves: &'static [&'static str]) -> impl Fn(usize) -> Reply {
+    move |ply| match moves.get(ply) {
+        Some(mv) => Reply::Move(mv),
+        None => Reply::Silent,
+    }
+}
+
+const SCHOLARS_MATE: &[&str] = &["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"];
+
+#[test]
+fn game_checkmate() {
+    let mut white = mock(line(SCHOLARS_MATE));
+    let mut black = mock(line(SCHOLARS_MATE));
+    let game = play_game(&mut white, &mut black, &UciGameSettings::default());
+    assert_eq!(game.result, UciGameResult::WhiteWins);
+    assert_eq!(game.termination, UciTermination::Checkmate);
+    assert_eq!(game.moves.len(), 7);
+    assert_eq!(game.moves[0].score, Some(UciScoreValue::Cp(25)));
+    assert_eq!(game.moves[0].depth, Some(3));
+    assert_eq!(game.reason(), "White mates");
+
+    let mut game = game;
+    for game_move in &mut game.moves {
+        game_move.time = Duration::from_millis(1500);
+    }
+    let pgn = game.to_pgn(&[("Event", "Test"), ("White", "A"), ("Black", "B")]);
+    let expected = r#"[Event "Test"]
+[Site "?"]
+[Date "?"]
+[Round "?"]
+[White "A"]
+[Black "B"]
+[Result "1-0"]
+[TimeControl "10+0.1"]
+[PlyCount "7"]
+[Termination "normal"]
+
+1. e4 {+0.25/3 1.500s} e5 {+0.25/3 1.500s} 2. Bc4 {+0.25/3 1.500s} Nc6
+{+0.25/3 1.500s} 3. Qh5 {+0.25/3 1.500s} Nf6 {+0.25/3 1.500s} 4. Qxf7#
+{+0.25/3 1.500s} {White mates} 1-0
+
+"#;
+    assert_eq!(pgn, expected);
+}
+
+#[test]
+fn game_illegal_move() {
+    let mut white = mock(line(&["e2e4", "", "d2d4"]));
+    let mut black = mock(line(&["", "e2e4"]));
+    let game = play_game(&mut white, &mut black, &UciGameSettings::default());
+    assert_eq!(game.result, UciGameResult::WhiteWins);
+    assert_eq!(
+        game.termination,
+        UciTermination::IllegalMove("e2e4".to_owned())
+    );
+    assert_eq!(game.termination.pgn_tag(), "rules infraction");
+    assert_eq!(game.reason(), "Black makes an illegal move: e2e4");
+
+    // `a1a1` is an illegal move, not a null move that passes the turn.
+    let mut white = mock(line(&["a1a1"]));
+    let mut black = mock(line(&[]));
+    let game = play_game(&mut white, &mut black, &UciGameSettings::default());
+    assert_eq!(game.result, UciGameResult::BlackWins);
+    assert_eq!(
+        game.termination,
+        UciTermination::IllegalMove("a1a1".to_owned())
+    );
+}
+
+#[test]
+fn game_time_forfeit() {
+    let settings = UciGameSettings {
+        time_control: "0.1+0".parse().unwrap(),
+        margin: Duration::from_millis(10),
+        ..Default::default()
+    };
+    let mut white = mock(|_| Reply::Silent);
+    let mut black = mock(|_| Reply::Silent);
+    let game = play_game(&mut white, &mut black, &settings);
+    assert_eq!(game.result, UciGameResult::BlackWins);
+    assert_eq!(game.termination, UciTermination::TimeForfeit);
+    assert_eq!(game.termination.pgn_tag(), "time forfeit");
+    assert_eq!(game.reason(), "White loses on time");
+}
+
+#[test]
+fn game_time_forfeit_insufficient_material() {
+    let settings = UciGameSettings {
+        init_pos: UciInitPos::Board("4k3/4p3/8/8/8/8/3N4/4K3 b - - 0 1".parse().unwrap()),
+        time_control: "0.1+0".parse().unwrap(),
+        margin: Duration::from_millis(10),
+        ..Default::default()
+    };
+    let mut white = mock(|_| Reply::Silent);
+    let mut black = mock(|_| Reply::Silent);
+    let game = play_game(&mut white, &mut black, &settings);
+    assert_eq!(game.result, UciGameResult::Draw);
+    assert_eq!(game.termination, UciTermination::TimeForfeit);
+    assert_eq!(
+        game.reason(),
+        "Draw by timeout vs insufficient mating material"
+    );
+}
+
+#[test]
+fn game_disconnect() {
+    let mut white = mock(line(&["e2e4"]));
+    let mut black = mock(|_| Reply::Disconnect);
+    let game = play_game(&mut white, &mut black, &UciGameSettings::default());
+    assert_eq!(game.result, UciGameResult::WhiteWins);
+    assert!(matches!(game.termination, UciTermination::Disconnected(_)));
+    assert_eq!(game.termination.pgn_tag(), "abandoned");
+}
+
+#[test]
+fn game_draws() {
+    let shuffle = &[
+        "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+    ];
+    let mut white = mock(line(shuffle));
+    let mut black = mock(line(shuffle));
+    let game = play_game(&mut white, &mut black, &UciGameSettings::default());
+    assert_eq!(game.result, UciGameResult::Draw);
+    assert_eq!(game.termination, UciTermination::Repetition);
+    assert_eq!(game.moves.len(), 8);
+
+    let board: Board = "4k3/8/8/8/8/8/8/R3K3 w - - 99 80".parse().unwrap();
+    let settings = UciGameSettings {
+        init_pos: UciInitPos::Board(board),
+        ..Default::default()
+    };
+    let mut white = mock(line(&["a1a2"]));
+    let mut black = mock(line(&[]));
+    let game = play_game(&mut white, &mut black, &settings);
+    assert_eq!(game.termination, UciTermination::FiftyMoves);
+    let pgn = game.to_pgn(&[]);
+    assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K3 w - - 99 80\"]\n"));
+    assert!(pgn.contains("\n80. Ra2 {"));
+
+    let board: Board = "4k3/8/8/8/8/8/8/R3K3 b - - 0 1".parse().unwrap();
+    let settings = UciGameSettings {
+        init_pos: UciInitPos::Board(board),
+        ..Default::default()
+    };
+    let mut white = mock(|_| Reply::Disconnect);
+    let mut black = mock(line(&["e8d7"]));
+    let game = play_game(&mut white, &mut black, &settings);
+    assert!(game.to_pgn(&[]).contains("\n1... Kd7 {"));
+}
+
+#[test]
+fn game_helpers() {
+    let insufficient = [
+        "8/8/4k3/8/8/8/8/4K3 w - - 0 1",
+        "8/8/4k3/8/8/8/2N5/4K3 w - - 0 1",
+        "8/8/4k3/8/8/2b5/3B4/4K3 w - - 0 1",
+    ];
+    for fen in insufficient {
+        assert!(is_insufficient_material(&fen.parse().unwrap()), "{}", fen);
+    }
+    let sufficient = [
+        "8/8/4k3/8/8/2b5/2B5/4K3 w - - 0 1",
+        "8/8/4k3/8/8/8/1NN5/4K3 w - - 0 1",
+        "8/8/4k3/8/8/8/P7/4K3 w - - 0 1",
+    ];
+    for fen in sufficient {
+        assert!(!is_insufficient_material(&fen.parse().unwrap()), "{}", fen);
+    }
+    let board: Board = "8/8/4k3/8/8/8/1NN5/4K3 w - - 0 1".parse().unwrap();
+    assert!(has_insufficient_material(&board, Color::Black));
+    assert!(!has_insufficient_material(&board, Color::White));
+    let board: Board = "8/8/4k3/8/8/1b6/2B5/4K3 w - - 0 1".parse().unwrap();
+    assert!(has_insufficient_material(&board, Color::White));
+
+    let tc: UciTimeControl = "60+0.5".parse().unwrap();
+    assert_eq!(tc.base, Duration::from_secs(60));
+    assert_eq!(tc.inc, Duration::from_millis(500));
+    assert_eq!(tc.to_string(), "60+0.5");
+    assert_eq!("40".parse::<UciTimeControl>().unwrap().inc, Duration::ZERO);
+    assert!("1+x".parse::<UciTimeControl>().is_err());
+
+    let epoch = std::time::UNIX_EPOCH;
+    assert_eq!(pgn_date(epoch), "1970.01.01");
+    let leap_day = epoch + Duration::from_secs(11016 * 86400);
+    assert_eq!(pgn_date(leap_day), "2000.02.29");
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use cozy_uci::command::UciCommand;
+use cozy_uci::message::UciMessage;
+use cozy_uci::remark::UciRemark;
+use cozy_uci::{UciFormatOptions, UciParseErrorKind};
+
+fn message_logs(log_path: &str) {
+    common::replay_log(log_path, |msg, options| {
+        let line = msg.format(options);
+        let msg_parsed = UciMessage::parse_from(&line, options).expect("failed to parse message");
+        assert_eq!(
+            msg, msg_parsed,
+            "message parsed in the wrong direction or not roundtripped: {}",
+            line
+        );
+    });
+}
+
+#[test]
+fn message_sf_w_game_1() {
+    message_logs("tests/uci_logs/sf_w_game_1.txt");
+}
+
+#[test]
+fn message_berserk_b_game_1() {
+    message_logs("tests/uci_logs/berserk_b_game_1.txt");
+}
+
+#[test]
+fn message_direction() {
+    let options = UciFormatOptions::default();
+    assert_eq!(
+        UciMessage::parse_from("isready", &options).unwrap(),
+        UciMessage::Command(UciCommand::IsReady)
+    );
+    assert_eq!(
+        UciMessage::parse_from("readyok", &options).unwrap(),
+        UciRemark::ReadyOk.into()
+    );
+
+    let err = UciMessage::parse_from("  register later", &options).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::UnknownMessageKind(kind) if kind == "register"));
+    assert_eq!(err.span, 2..10);
+
+    let err = UciMessage::parse_from("bestmove e2e4 ponder", &options).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::UnexpectedEnd));
+
+    let err = UciMessage::parse_from("", &options).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::UnexpectedEnd));
+}
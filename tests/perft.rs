@@ -0,0 +1,211 @@
+mod common;
+
+use std::io::prelude::*;
+use std::time::Duration;
+
+use cozy_chess::*;
+use cozy_uci::command::{UciCommand, UciGoParams, UciInitPos};
+use cozy_uci::perft::*;
+use cozy_uci::UciFormatOptions;
+
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+
+fn is_en_passant(board: &Board, mv: Move) -> bool {
+    board.pieces(Piece::Pawn).has(mv.from)
+        && mv.from.file() != mv.to.file()
+        && !board.occupied().has(mv.to)
+}
+
+fn buggy_perft(board: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut nodes = 0;
+    board.generate_moves(|mvs| {
+        for mv in mvs {
+            if !is_en_passant(board, mv) {
+                let mut child = board.clone();
+                child.play_unchecked(mv);
+                nodes += buggy_perft(&child, depth - 1);
+            }
+        }
+        false
+    });
+    nodes
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Bug {
+    None,
+    SkipsEnPassant,
+    /// Adds a node to the first move of the root divide only.
+    MiscountsRoot,
+}
+
+fn mock_engine(input: impl BufRead, mut output: impl Write, bug: Bug) {
+    let options = UciFormatOptions::default();
+    let mut board = Board::default();
+    let mut at_root = true;
+    for line in input.lines() {
+        match UciCommand::parse_from(&line.unwrap(), &options).unwrap() {
+            UciCommand::Position { init_pos, moves } => {
+                at_root = moves.is_empty();
+                board = init_pos.into();
+                for mv in moves {
+                    board.play(mv);
+                }
+            }
+            UciCommand::Go(UciGoParams {
+                perft: Some(depth), ..
+            }) => {
+                writeln!(output, "info string NNUE evaluation enabled").unwrap();
+                let mut total = 0;
+                board.generate_moves(|mvs| {
+                    for mv in mvs {
+                        let skips_en_passant = bug == Bug::SkipsEnPassant;
+                        if skips_en_passant && is_en_passant(&board, mv) {
+                            continue;
+                        }
+                        let mut child = board.clone();
+                        child.play_unchecked(mv);
+                        let mut nodes = match skips_en_passant {
+                            true => buggy_perft(&child, depth - 1),
+                            false => perft(&child, depth - 1),
+                        };
+                        if bug == Bug::MiscountsRoot && at_root && total == 0 {
+                            nodes += 1;
+                        }
+                        writeln!(output, "{}: {}", mv, nodes).unwrap();
+                        total += nodes;
+                    }
+                    false
+                });
+                writeln!(output, "\nNodes searched: {}\n", total).unwrap();
+            }
+            UciCommand::Quit => break,
+            _ => {}
+        }
+    }
+}
+
+fn run_mock(bug: Bug, depth: u32) -> Result<Option<UciPerftMismatch>, UciPerftError> {
+    let (mut engine, mock) =
+        common::spawn_mock_engine(move |input, output| mock_engine(input, output, bug));
+    let init_pos = UciInitPos::Board(POSITION_3.parse().unwrap());
+    let timeout = Duration::from_secs(10);
+    let mismatch = find_perft_mismatch(&mut engine, &init_pos, &[], depth, timeout);
+    engine.quit(timeout).unwrap();
+    mock.join().unwrap();
+    mismatch
+}
+
+#[test]
+fn perft_counts() {
+    let startpos = Board::default();
+    assert_eq!(perft(&startpos, 0), 1);
+    assert_eq!(perft(&startpos, 3), 8902);
+    assert_eq!(perft(&POSITION_3.parse().unwrap(), 3), 2812);
+
+    let divide = UciPerftDivide::compute(&startpos, 2, false);
+    assert_eq!(divide.moves.len(), 20);
+    assert_eq!(divide.nodes, 400);
+    assert!(divide.moves.iter().all(|&(_, nodes)| nodes == 20));
+}
+
+#[test]
+fn divide_castling_in_uci() {
+    let board: Board = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+    let standard = UciPerftDivide::compute(&board, 1, false);
+    let castles = |divide: &UciPerftDivide, mv: &str| {
+        divide.moves.iter().any(|&(m, _)| m == mv.parse().unwrap())
+    };
+    assert!(castles(&standard, "e1g1"));
+    assert!(castles(&standard, "e1c1"));
+    let chess960 = UciPerftDivide::compute(&board, 1, true);
+    assert!(castles(&chess960, "e1h1"));
+    assert!(castles(&chess960, "e1a1"));
+}
+
+#[test]
+fn divide_lines() {
+    let e2e4 = "e2e4".parse().unwrap();
+    assert_eq!(
+        UciDivideLine::parse_from("e2e4: 20").unwrap(),
+        UciDivideLine::Move(e2e4, 20)
+    );
+    assert_eq!(
+        UciDivideLine::parse_from("e2e4 : 20").unwrap(),
+        UciDivideLine::Move(e2e4, 20)
+    );
+    assert_eq!(
+        UciDivideLine::parse_from("e2e4 20").unwrap(),
+        UciDivideLine::Move(e2e4, 20)
+    );
+    assert_eq!(
+        UciDivideLine::parse_from("Nodes searched: 197281").unwrap(),
+        UciDivideLine::Nodes(197281)
+    );
+    assert_eq!(
+        UciDivideLine::parse_from("Total: 8902").unwrap(),
+        UciDivideLine::Nodes(8902)
+    );
+    assert!(UciDivideLine::parse_from("info depth 1").is_err());
+    assert!(UciDivideLine::parse_from("e2e4: twenty").is_err());
+}
+
+#[test]
+fn go_perft() {
+    let options = UciFormatOptions::default();
+    let cmd = UciCommand::parse_from("go perft 5", &options).unwrap();
+    assert_eq!(
+        cmd,
+        UciCommand::Go(UciGoParams {
+            perft: Some(5),
+            ..Default::default()
+        })
+    );
+    assert_eq!(cmd.format(&options), "go perft 5");
+}
+
+#[test]
+fn perft_matches() {
+    assert_eq!(run_mock(Bug::None, 3).unwrap(), None);
+    assert!(matches!(
+        run_mock(Bug::None, 0),
+        Err(UciPerftError::ZeroDepth)
+    ));
+}
+
+#[test]
+fn perft_narrows_to_missing_move() {
+    let mismatch = run_mock(Bug::SkipsEnPassant, 4)
+        .unwrap()
+        .expect("mismatch not found");
+    let UciPerftMismatchKind::Missing(mv) = mismatch.kind else {
+        panic!("unexpected mismatch {:?}", mismatch.kind);
+    };
+    assert!(is_en_passant(&mismatch.board, mv));
+    assert_eq!(mismatch.depth as usize + mismatch.moves.len(), 4);
+
+    let mut board: Board = POSITION_3.parse().unwrap();
+    for &mv in &mismatch.moves {
+        board.play(mv);
+    }
+    assert_eq!(board, mismatch.board);
+}
+
+#[test]
+fn perft_reports_move_when_children_agree() {
+    let mismatch = run_mock(Bug::MiscountsRoot, 3)
+        .unwrap()
+        .expect("mismatch not found");
+    assert_eq!(mismatch.moves, []);
+    assert_eq!(mismatch.depth, 3);
+    let UciPerftMismatchKind::Nodes {
+        expected, actual, ..
+    } = mismatch.kind
+    else {
+        panic!("unexpected mismatch {:?}", mismatch.kind);
+    };
+    assert_eq!(actual, expected + 1);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use cozy_chess::*;
+use cozy_uci::command::UciCommand;
+use cozy_uci::message::UciMessage;
+use cozy_uci::pov::{UciPovDetector, UciScorePov};
+use cozy_uci::remark::{UciInfo, UciRemark, UciScore, UciScoreKind};
+
+fn detect_pov(log_path: &str, white_pov: bool) -> UciPovDetector {
+    let mut detector = UciPovDetector::new();
+    let mut side_to_move = Color::White;
+    common::replay_log(log_path, |msg, _| match msg {
+        UciMessage::Command(cmd) => {
+            if let UciCommand::Position { init_pos, moves } = &cmd {
+                let board: Board = init_pos.clone().into();
+                side_to_move = match moves.len() % 2 {
+                    0 => board.side_to_move(),
+                    _ => !board.side_to_move(),
+                };
+            }
+            detector.update_command(&cmd);
+        }
+        UciMessage::Remark(mut rmk) => {
+            if let (UciRemark::Info(info), true) = (&mut rmk, white_pov) {
+                info.convert_score_pov(side_to_move, Color::White);
+            }
+            detector.update_remark(&rmk);
+        }
+    });
+    detector
+}
+
+#[test]
+fn detect_side_to_move_pov() {
+    let detector = detect_pov("tests/uci_logs/sf_b_game_1.txt", false);
+    assert_eq!(detector.verdict(), Some(UciScorePov::SideToMove));
+    assert_eq!(detector.white, 0);
+
+    // A drawn game never gets unbalanced enough to tell.
+    let detector = detect_pov("tests/uci_logs/berserk_b_game_1.txt", false);
+    assert_eq!(detector.verdict(), None);
+}
+
+#[test]
+fn detect_white_pov() {
+    let detector = detect_pov("tests/uci_logs/sf_b_game_1.txt", true);
+    assert_eq!(detector.verdict(), Some(UciScorePov::White));
+    assert_eq!(detector.side_to_move, 0);
+}
+
+#[test]
+fn score_pov_conversion() {
+    let score = UciScore {
+        cp: Some(-120),
+        mate: None,
+        wdl: Some((50, 300, 650)),
+        kind: UciScoreKind::LowerBound,
+    };
+    let flipped = UciScore {
+        cp: Some(120),
+        mate: None,
+        wdl: Some((650, 300, 50)),
+        kind: UciScoreKind::UpperBound,
+    };
+    assert_eq!(score.flipped(), flipped);
+    assert_eq!(score.convert_pov(Color::Black, Color::White), flipped);
+    assert_eq!(score.convert_pov(Color::White, Color::White), score);
+
+    let mut info = UciInfo {
+        score: Some(score),
+        ..Default::default()
+    };
+    let mut board = Board::default();
+    assert_eq!(info.white_score(&board), Some(score));
+    board.play("e2e4".parse().unwrap());
+    assert_eq!(info.white_score(&board), Some(flipped));
+    info.convert_score_pov(Color::Black, Color::White);
+    assert_eq!(info.score, Some(flipped));
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::sync::{Arc, Mutex};
+
+use cozy_uci::proxy::{run_proxy, UciTranscript};
+
+/// A writer that can be inspected after being moved into the proxy.
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn mock_engine(input: impl BufRead, mut output: impl Write) {
+    for line in input.lines() {
+        match line.unwrap().trim() {
+            "uci" => write!(output, "id name Mock\r\nuciok\n").unwrap(),
+            "isready" => writeln!(output, "readyok").unwrap(),
+            "go depth 1" => writeln!(output, "info depth 1 pv e2e4\nbestmove e2e4").unwrap(),
+            "quit" => break,
+            _ => writeln!(output, "info string what?").unwrap(),
+        }
+    }
+}
+
+/// A writer whose writes always fail.
+struct BrokenLog;
+
+impl Write for BrokenLog {
+    fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("disk full"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+const GUI_INPUT: &str = "uci\nisready\r\ntune Futility 150\ngo depth 1\nquit\n";
+
+const GUI_OUTPUT: &str =
+    "id name Mock\r\nuciok\nreadyok\ninfo string what?\ninfo depth 1 pv e2e4\nbestmove e2e4\n";
+
+fn run<T: Write + Send + 'static>(transcript: UciTranscript<T>) -> String {
+    let (rmk_reader, cmd_writer, engine) = common::spawn_mock(mock_engine);
+    let gui_output = SharedBuf::default();
+    run_proxy(
+        BufReader::new(GUI_INPUT.as_bytes()),
+        gui_output.clone(),
+        rmk_reader,
+        cmd_writer,
+        transcript,
+    )
+    .unwrap();
+    engine.join().unwrap();
+    let gui_output = gui_output.0.lock().unwrap().clone();
+    String::from_utf8(gui_output).unwrap()
+}
+
+#[test]
+fn proxy_forwards_and_records() {
+    let log = SharedBuf::default();
+    let mut transcript = UciTranscript::new(log.clone());
+    transcript.comment("session started").unwrap();
+    assert_eq!(run(transcript), GUI_OUTPUT);
+
+    let log = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+    let mut lines = log.lines();
+    assert_eq!(lines.next(), Some("# session started"));
+    let mut messages = Vec::new();
+    while let Some(line) = lines.next() {
+        let comment = line.strip_prefix("# ").expect("message without timestamp");
+        let message = lines.next().expect("timestamp without message");
+        let (timestamp, annotation) = comment.split_once(' ').unwrap_or((comment, ""));
+        assert!(
+            timestamp.parse::<f64>().is_ok(),
+            "bad timestamp {:?}",
+            timestamp
+        );
+        match message {
+            ">tune Futility 150" => assert!(annotation.contains("unknown message kind")),
+            _ => assert_eq!(annotation, "", "unexpected annotation on {:?}", message),
+        }
+        messages.push(message);
+    }
+    for expected in [
+        ">uci",
+        ">isready",
+        ">tune Futility 150",
+        ">go depth 1",
+        ">quit",
+        "<id name Mock",
+        "<uciok",
+        "<readyok",
+        "<info string what?",
+        "<info depth 1 pv e2e4",
+        "<bestmove e2e4",
+    ] {
+        let i = messages
+            .iter()
+            .position(|&m| m == expected)
+            .unwrap_or_else(|| panic!("missing {:?} in {}", expected, log));
+        messages.remove(i);
+    }
+    assert_eq!(messages, Vec::<&str>::new());
+}
+
+#[test]
+fn proxy_forwards_when_log_fails() {
+    assert_eq!(run(UciTranscript::new(BrokenLog)), GUI_OUTPUT);
+}
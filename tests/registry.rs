@@ -0,0 +1,204 @@
+use std::fmt::{self, Formatter};
+
+use cozy_uci::command::UciCommand;
+use cozy_uci::message::UciMessage;
+use cozy_uci::registry::{UciExtension, UciRegistry};
+use cozy_uci::remark::UciRemark;
+use cozy_uci::{
+    UciFormatError, UciFormatErrorKind, UciFormatOptions, UciParseError, UciParseErrorKind,
+    UciTokenStream,
+};
+
+/// `tune <param> <value>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Tune {
+    param: String,
+    value: i32,
+}
+
+impl UciExtension for Tune {
+    fn kind() -> &'static str {
+        "tune"
+    }
+
+    fn parse(s: &mut UciTokenStream, _: &UciFormatOptions) -> Result<Self, UciParseError> {
+        let param = s.read_token()?.0.to_owned();
+        let (tok, span) = s.read_token()?;
+        let value = tok
+            .parse()
+            .map_err(|e| UciParseErrorKind::IntParseError(e).spans(span))?;
+        Ok(Self { param, value })
+    }
+
+    fn format_args(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, " {} {}", self.param, self.value)
+    }
+
+    fn check(&self) -> Result<(), UciFormatError> {
+        if self.param.is_empty() {
+            return Err(UciFormatErrorKind::EmptyToken.in_field("param"));
+        }
+        Ok(())
+    }
+}
+
+/// `dumptt`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct DumpTt;
+
+impl UciExtension for DumpTt {
+    fn kind() -> &'static str {
+        "dumptt"
+    }
+
+    fn parse(_: &mut UciTokenStream, _: &UciFormatOptions) -> Result<Self, UciParseError> {
+        Ok(Self)
+    }
+
+    fn format_args(&self, _: &mut Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// `datagen <positions>`, reported by the engine as it generates data.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Datagen(u64);
+
+impl UciExtension for Datagen {
+    fn kind() -> &'static str {
+        "datagen"
+    }
+
+    fn parse(s: &mut UciTokenStream, _: &UciFormatOptions) -> Result<Self, UciParseError> {
+        Ok(Self(s.read_type()?))
+    }
+
+    fn format_args(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, " {}", self.0)
+    }
+}
+
+fn registry() -> UciRegistry {
+    let mut registry = UciRegistry::new();
+    registry.register_command::<Tune>();
+    registry.register_command::<DumpTt>();
+    registry.register_remark::<Datagen>();
+    registry
+}
+
+#[test]
+fn custom_commands() {
+    let registry = registry();
+    let options = UciFormatOptions::default();
+    let cmd = UciCommand::parse_with("tune  Futility 150", &options, &registry).unwrap();
+    let UciCommand::Custom(ext) = &cmd else {
+        panic!("unexpected command {:?}", cmd);
+    };
+    assert_eq!(
+        ext.downcast_ref::<Tune>(),
+        Some(&Tune {
+            param: "Futility".to_owned(),
+            value: 150
+        })
+    );
+    assert!(ext.downcast_ref::<DumpTt>().is_none());
+    assert_eq!(cmd.format(&options), "tune Futility 150");
+    assert_eq!(cmd.clone(), cmd);
+    assert_ne!(
+        cmd,
+        UciCommand::parse_with("tune Futility 151", &options, &registry).unwrap()
+    );
+
+    let cmd = UciCommand::parse_with("dumptt", &options, &registry).unwrap();
+    assert_eq!(cmd, UciCommand::Custom(Box::new(DumpTt)));
+    assert_eq!(cmd.format(&options), "dumptt");
+}
+
+#[test]
+fn custom_remarks() {
+    let registry = registry();
+    let options = UciFormatOptions::default();
+    let rmk = UciRemark::parse_with("datagen 1000000", &options, &registry).unwrap();
+    assert_eq!(rmk, UciRemark::Custom(Box::new(Datagen(1000000))));
+    assert_eq!(rmk.format(&options), "datagen 1000000");
+
+    let err = UciCommand::parse_with("datagen 5", &options, &registry).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::UnknownMessageKind(kind) if kind == "datagen"));
+}
+
+#[test]
+fn custom_errors() {
+    let registry = registry();
+    let options = UciFormatOptions::default();
+    let err = UciCommand::parse_with("tune Futility high", &options, &registry).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::IntParseError(_)));
+    assert_eq!(err.span, 14..18);
+
+    let err = UciCommand::parse_with("dumptt now", &options, &registry).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::UnexpectedToken(tok) if tok == "now"));
+    assert_eq!(err.span, 7..10);
+
+    let err = UciCommand::parse_with("tune", &options, &registry).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::UnexpectedEnd));
+
+    let err = UciCommand::parse_from("tune x 1", &UciFormatOptions::default()).unwrap_err();
+    assert!(matches!(err.kind, UciParseErrorKind::UnknownMessageKind(kind) if kind == "tune"));
+
+    let empty = UciCommand::Custom(Box::new(Tune {
+        param: String::new(),
+        value: 0,
+    }));
+    let err = empty.try_format(&options).unwrap_err();
+    assert_eq!(err.field, "param");
+    assert_eq!(err.kind, UciFormatErrorKind::EmptyToken);
+}
+
+#[test]
+fn custom_message_direction() {
+    let registry = registry();
+    let options = UciFormatOptions::default();
+    assert!(matches!(
+        UciMessage::parse_with("dumptt", &options, &registry),
+        Ok(UciMessage::Command(UciCommand::Custom(_)))
+    ));
+    assert!(matches!(
+        UciMessage::parse_with("datagen 3", &options, &registry),
+        Ok(UciMessage::Remark(UciRemark::Custom(_)))
+    ));
+
+    let mut registry = registry;
+    registry.register_remark::<DumpTt>();
+    let err = UciMessage::parse_with("dumptt", &options, &registry).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        UciParseErrorKind::AmbiguousMessageKind(_)
+    ));
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ShadowedIsReady;
+
+impl UciExtension for ShadowedIsReady {
+    fn kind() -> &'static str {
+        "isready"
+    }
+
+    fn parse(_: &mut UciTokenStream, _: &UciFormatOptions) -> Result<Self, UciParseError> {
+        Ok(Self)
+    }
+
+    fn format_args(&self, _: &mut Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
+
+#[test]
+fn builtins_take_precedence() {
+    let mut registry = registry();
+    let options = UciFormatOptions::default();
+    registry.register_command::<ShadowedIsReady>();
+    assert_eq!(
+        UciCommand::parse_with("isready", &options, &registry).unwrap(),
+        UciCommand::IsReady
+    );
+}
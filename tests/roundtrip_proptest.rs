@@ -0,0 +1,26 @@
+use cozy_uci::arbitrary::arb_format_options;
+use cozy_uci::command::UciCommand;
+use cozy_uci::remark::UciRemark;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn roundtrip_command(
+        (options, cmd) in arb_format_options()
+            .prop_flat_map(|options| (Just(options.clone()), any_with::<UciCommand>(options)))
+    ) {
+        let cmd_str = cmd.format(&options);
+        let cmd_roundtripped = UciCommand::parse_from(&cmd_str, &options);
+        prop_assert_eq!(Ok(cmd), cmd_roundtripped.map_err(|e| e.to_string()), "{}", cmd_str);
+    }
+
+    #[test]
+    fn roundtrip_remark(
+        (options, rmk) in arb_format_options()
+            .prop_flat_map(|options| (Just(options.clone()), any_with::<UciRemark>(options)))
+    ) {
+        let rmk_str = rmk.format(&options);
+        let rmk_roundtripped = UciRemark::parse_from(&rmk_str, &options);
+        prop_assert_eq!(Ok(rmk), rmk_roundtripped.map_err(|e| e.to_string()), "{}", rmk_str);
+    }
+}
@@ -0,0 +1,98 @@
+use cozy_chess::*;
+use cozy_uci::command::UciInitPos;
+use cozy_uci::remark::{UciBestMove, UciRemark};
+use cozy_uci::san::{UciSanFormatter, UciSanInfo};
+use cozy_uci::{UciFormatOptions, UCI_NULL_MOVE};
+
+fn moves(moves: &str) -> Vec<Move> {
+    moves
+        .split_whitespace()
+        .map(|mv| mv.parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn san_pv() {
+    let options = UciFormatOptions::default();
+    let formatter = UciSanFormatter::new(&UciInitPos::StartPos, &[], &options).unwrap();
+    let rmk = UciRemark::parse_from(
+        "info depth 3 currmove g1f3 pv g1f3 d7d5 d2d4 refutation e2e4 d7d5",
+        &options,
+    )
+    .unwrap();
+    let UciRemark::Info(info) = rmk else {
+        unreachable!()
+    };
+    assert_eq!(
+        formatter.info(&info).unwrap(),
+        UciSanInfo {
+            pv: Some("1. Nf3 d5 2. d4".to_owned()),
+            currmove: Some("1. Nf3".to_owned()),
+            refutation: Some("1. e4 d5".to_owned()),
+            currline: None,
+        }
+    );
+}
+
+#[test]
+fn san_black_to_move_and_castling() {
+    let options = UciFormatOptions::default();
+    let played = moves("e2e4 e7e5 g1f3 b8c6 f1c4 g8f6 e1g1");
+    let mut formatter = UciSanFormatter::new(&UciInitPos::StartPos, &played, &options).unwrap();
+    assert_eq!(
+        formatter.moves("pv", &moves("f8c5 d2d3 e8g8")).unwrap(),
+        "4... Bc5 5. d3 O-O"
+    );
+    formatter.figurine = true;
+    assert_eq!(
+        formatter
+            .best_move(
+                UciBestMove::Move("f6e4".parse().unwrap()),
+                Some("f1e1".parse().unwrap())
+            )
+            .unwrap(),
+        "4... ♘xe4 5. ♖e1"
+    );
+    assert_eq!(
+        formatter.best_move(UciBestMove::None, None).unwrap(),
+        "(none)"
+    );
+
+    let err = formatter.moves("pv", &moves("f8c5 f8c5")).unwrap_err();
+    assert_eq!((err.field, err.ply), ("pv", 1));
+}
+
+#[test]
+fn san_chess960_castling() {
+    let options = UciFormatOptions {
+        chess960: true,
+        ..Default::default()
+    };
+    let init_pos = UciInitPos::Board(
+        "nbbrkqnr/pppppppp/8/8/8/8/PPPPPPPP/NBBRKQNR w HDhd - 0 1"
+            .parse()
+            .unwrap(),
+    );
+    let played = moves("g1f3 g8f6 g2g3 g7g6 f1g2 f8g7");
+    let formatter = UciSanFormatter::new(&init_pos, &played, &options).unwrap();
+    assert_eq!(formatter.moves("pv", &moves("e1h1")).unwrap(), "4. O-O");
+    assert_eq!(formatter.parse_moves("O-O").unwrap(), moves("e1h1"));
+}
+
+#[test]
+fn parse_san() {
+    let options = UciFormatOptions::default();
+    let played = moves("e2e4 e7e5 g1f3 b8c6 f1c4 g8f6");
+    let formatter = UciSanFormatter::new(&UciInitPos::StartPos, &played, &options).unwrap();
+    assert_eq!(
+        formatter.parse_moves("4. O-O Nxe4 5. ♖e1 --").unwrap(),
+        [moves("e1g1 f6e4 f1e1"), vec![UCI_NULL_MOVE]].concat()
+    );
+    assert_eq!(
+        formatter.parse_searchmoves("Nc3 d3 O-O").unwrap(),
+        moves("b1c3 d2d3 e1g1")
+    );
+
+    let err = formatter.parse_searchmoves("Nc3 Nd5").unwrap_err();
+    assert_eq!(err.span, 4..7);
+}
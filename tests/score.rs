@@ -0,0 +1,65 @@
+use cozy_uci::remark::{UciScore, UciScoreKind, UciScoreValue};
+
+use UciScoreValue::*;
+
+#[test]
+fn score_ordering() {
+    let ascending = [
+        Mate(0),
+        Mate(-1),
+        Mate(-12),
+        Cp(i32::MIN),
+        Cp(-350),
+        Cp(0),
+        Cp(20),
+        Cp(i32::MAX),
+        Mate(30),
+        Mate(2),
+        Mate(1),
+    ];
+    for (i, a) in ascending.iter().enumerate() {
+        for (j, b) in ascending.iter().enumerate() {
+            assert_eq!(a.cmp(b), i.cmp(&j), "{:?} vs {:?}", a, b);
+        }
+    }
+    assert_eq!(ascending.iter().max(), Some(&Mate(1)));
+}
+
+#[test]
+fn score_negation() {
+    assert_eq!(-Cp(35), Cp(-35));
+    assert_eq!(-Cp(i32::MIN), Cp(i32::MAX));
+    assert_eq!(-Mate(3), Mate(-3));
+    assert_eq!(-Mate(-3), Mate(3));
+    assert!(-Mate(3) < -Cp(500));
+    assert!(-Cp(20) < -Cp(-20));
+}
+
+#[test]
+fn score_conversions() {
+    let score = UciScore::from(Mate(-4));
+    assert_eq!(
+        score,
+        UciScore {
+            cp: None,
+            mate: Some(-4),
+            wdl: None,
+            kind: UciScoreKind::Exact,
+        }
+    );
+    assert_eq!(score.value(), Some(Mate(-4)));
+    assert_eq!(Cp(-20).to_string(), "cp -20");
+    assert_eq!(Mate(5).to_string(), "mate 5");
+
+    let both = UciScore {
+        cp: Some(10),
+        ..score
+    };
+    assert_eq!(both.value(), None);
+    let neither = UciScore {
+        mate: None,
+        wdl: Some((300, 400, 300)),
+        ..score
+    };
+    assert_eq!(neither.value(), None);
+}
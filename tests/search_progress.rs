@@ -0,0 +1,109 @@
+mod common;
+
+use std::time::Duration;
+
+use cozy_chess::util::parse_uci_move;
+use cozy_chess::*;
+use cozy_uci::command::UciCommand;
+use cozy_uci::message::UciMessage;
+use cozy_uci::progress::{SearchAnomaly, SearchProgress};
+use cozy_uci::remark::{UciInfo, UciRemark, UciScore, UciScoreKind};
+use cozy_uci::UciFormatOptions;
+
+fn progress_logs(log_path: &str) {
+    let mut root = Board::default();
+    let mut progress = None;
+    common::replay_log(log_path, |msg, options| match msg {
+        UciMessage::Command(UciCommand::Position { init_pos, moves }) => {
+            root = init_pos.into();
+            for mv in moves {
+                let mv = match options.chess960 {
+                    true => mv,
+                    false => parse_uci_move(&root, &mv.to_string()).unwrap(),
+                };
+                root.play(mv);
+            }
+        }
+        UciMessage::Command(UciCommand::Go(_)) => {
+            progress = Some(SearchProgress::new(root.clone(), options))
+        }
+        UciMessage::Remark(UciRemark::Info(info)) => {
+            if let Some(progress) = &mut progress {
+                progress.update(&info);
+            }
+        }
+        UciMessage::Remark(UciRemark::BestMove { .. }) => {
+            let progress = progress.take().expect("bestmove without search");
+            assert_eq!(progress.anomalies, [], "unexpected anomalies in search");
+            assert!(progress.best_move().is_some(), "search has no best move");
+            assert!(progress.nps.is_some(), "search has no nps");
+        }
+        _ => {}
+    });
+}
+
+#[test]
+fn progress_sf_w_game_1() {
+    progress_logs("tests/uci_logs/sf_w_game_1.txt");
+}
+
+#[test]
+fn progress_sf_b_game_2() {
+    progress_logs("tests/uci_logs/sf_b_game_2.txt");
+}
+
+#[test]
+fn progress_berserk_w_game_1() {
+    progress_logs("tests/uci_logs/berserk_w_game_1.txt");
+}
+
+#[test]
+fn progress_anomalies() {
+    let options = UciFormatOptions::default();
+    let mut progress = SearchProgress::new(Board::default(), &options);
+    let score = UciScore {
+        cp: Some(20),
+        mate: None,
+        wdl: None,
+        kind: UciScoreKind::Exact,
+    };
+    progress.update(&UciInfo {
+        depth: Some(1),
+        nodes: Some(2000),
+        time: Some(Duration::from_millis(4)),
+        pv: Some(vec!["e2e4".parse().unwrap()]),
+        score: Some(score),
+        ..Default::default()
+    });
+    assert_eq!(progress.nps, Some(500000));
+    assert_eq!(progress.anomalies, []);
+
+    progress.update(&UciInfo {
+        depth: Some(2),
+        nodes: Some(1000),
+        pv: Some(vec!["e2e5".parse().unwrap()]),
+        score: Some(score),
+        ..Default::default()
+    });
+    assert_eq!(
+        progress.anomalies,
+        [
+            SearchAnomaly::NodesDecreased {
+                previous: 2000,
+                current: 1000
+            },
+            SearchAnomaly::IllegalPvMove {
+                mv: "e2e5".parse().unwrap()
+            },
+        ]
+    );
+    assert_eq!(progress.depth, Some(2));
+    assert_eq!(progress.score_history.len(), 2);
+    // Without `nps`, the rate follows the latest nodes and time.
+    assert_eq!(progress.nps, Some(250000));
+    progress.update(&UciInfo {
+        time: Some(Duration::from_millis(8)),
+        ..Default::default()
+    });
+    assert_eq!(progress.nps, Some(125000));
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use cozy_uci::message::UciMessage;
+use cozy_uci::session::{UciProtocolViolation, UciSession, UciSessionState};
+
+fn violations(lines: &[&str]) -> Vec<(usize, UciProtocolViolation)> {
+    let mut session = UciSession::new();
+    let mut violations = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let msg = UciMessage::parse_from(line, session.options()).expect("failed to parse message");
+        if let Err(violation) = session.update(&msg) {
+            violations.push((i, violation));
+        }
+    }
+    violations
+}
+
+fn lint_log(log_path: &str) {
+    let mut session = UciSession::new();
+    let mut violations = Vec::new();
+    common::replay_log(log_path, |msg, _| {
+        if let Err(violation) = session.update(&msg) {
+            violations.push(violation);
+        }
+    });
+    assert_eq!(violations, vec![]);
+}
+
+#[test]
+fn session_sf_b_game_1() {
+    lint_log("tests/uci_logs/sf_b_game_1.txt");
+}
+
+#[test]
+fn session_sf_w_game_2() {
+    lint_log("tests/uci_logs/sf_w_game_2.txt");
+}
+
+#[test]
+fn session_berserk_w_game_1() {
+    lint_log("tests/uci_logs/berserk_w_game_1.txt");
+}
+
+const HANDSHAKE: [&str; 5] = [
+    "uci",
+    "id name Mock",
+    "option name Hash type spin default 16 min 1 max 1024",
+    "uciok",
+    "isready",
+];
+
+fn after_handshake(lines: &[&str]) -> Vec<(usize, UciProtocolViolation)> {
+    let mut all = HANDSHAKE.to_vec();
+    all.extend(lines);
+    violations(&all)
+        .into_iter()
+        .map(|(i, v)| (i - HANDSHAKE.len(), v))
+        .collect()
+}
+
+#[test]
+fn session_states() {
+    let mut session = UciSession::new();
+    let mut feed = |line: &str| {
+        let msg = UciMessage::parse_from(line, session.options()).unwrap();
+        session.update(&msg).unwrap();
+        session.state()
+    };
+    assert_eq!(feed("uci"), UciSessionState::AwaitingUciOk);
+    assert_eq!(feed("uciok"), UciSessionState::Idle);
+    assert_eq!(feed("isready"), UciSessionState::Idle);
+    assert_eq!(feed("readyok"), UciSessionState::Idle);
+    assert_eq!(feed("position startpos"), UciSessionState::Idle);
+    assert_eq!(feed("go ponder movetime 100"), UciSessionState::Pondering);
+    assert_eq!(feed("info depth 1"), UciSessionState::Pondering);
+    assert_eq!(feed("ponderhit"), UciSessionState::Searching);
+    assert_eq!(feed("bestmove e2e4"), UciSessionState::Idle);
+    assert_eq!(feed("quit"), UciSessionState::Quit);
+}
+
+#[test]
+fn gui_violations() {
+    use UciProtocolViolation::*;
+
+    assert_eq!(violations(&["isready"]), vec![(0, BeforeUci("isready"))]);
+    assert_eq!(
+        violations(&["uci", "position startpos", "uciok"]),
+        vec![(1, BeforeUciOk("position"))]
+    );
+    assert_eq!(
+        violations(&["uci", "uciok", "position startpos", "go depth 1"]),
+        vec![(3, GoBeforeIsReady)]
+    );
+    assert_eq!(
+        after_handshake(&["go depth 1"]),
+        vec![(0, GoWithoutPosition)]
+    );
+    assert_eq!(
+        after_handshake(&[
+            "position startpos",
+            "go infinite",
+            "position startpos",
+            "go depth 3"
+        ]),
+        vec![(2, DuringSearch("position")), (3, DuringSearch("go"))]
+    );
+    assert_eq!(
+        after_handshake(&[
+            "setoption name hash value 32",
+            "setoption name HASH value 64"
+        ]),
+        vec![]
+    );
+    assert_eq!(
+        after_handshake(&["stop", "ponderhit"]),
+        vec![(1, NotSearching("ponderhit"))]
+    );
+    assert_eq!(
+        after_handshake(&[
+            "position startpos",
+            "go movetime 100",
+            "bestmove e2e4",
+            "stop",
+            "isready",
+            "readyok",
+        ]),
+        vec![]
+    );
+    assert_eq!(
+        after_handshake(&["position startpos", "go depth 1", "ponderhit"]),
+        vec![(2, NotPondering)]
+    );
+    assert_eq!(
+        after_handshake(&[
+            "setoption name Hash value 64",
+            "setoption name Threads value 4"
+        ]),
+        vec![(1, UnknownOption("Threads".to_owned()))]
+    );
+    assert_eq!(
+        after_handshake(&["quit", "isready"]),
+        vec![(1, AfterQuit("isready"))]
+    );
+}
+
+#[test]
+fn engine_violations() {
+    use UciProtocolViolation::*;
+
+    assert_eq!(
+        after_handshake(&["readyok", "readyok"]),
+        vec![(1, UnsolicitedReadyOk)]
+    );
+    assert_eq!(
+        after_handshake(&["id name Mock", "uciok"]),
+        vec![(0, OutsideHandshake("id")), (1, OutsideHandshake("uciok"))]
+    );
+    assert_eq!(
+        after_handshake(&[
+            "info string loaded network",
+            "position startpos",
+            "go depth 1",
+            "info depth 1 pv e2e4",
+            "bestmove e2e4",
+            "info depth 2 pv e2e4",
+            "bestmove e2e4",
+        ]),
+        vec![
+            (5, RemarkWhileIdle("info")),
+            (6, RemarkWhileIdle("bestmove"))
+        ]
+    );
+    assert_eq!(
+        after_handshake(&["position startpos", "go infinite", "bestmove e2e4"]),
+        vec![(2, BestMoveBeforeStop)]
+    );
+    assert_eq!(
+        after_handshake(&["position startpos", "go ponder", "bestmove e2e4"]),
+        vec![(2, BestMoveWhilePondering)]
+    );
+    assert_eq!(
+        after_handshake(&["position startpos", "go ponder", "stop", "bestmove e2e4"]),
+        vec![]
+    );
+}
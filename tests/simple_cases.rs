@@ -1,9 +1,11 @@
 use std::time::Duration;
 
 use cozy_chess::*;
-use cozy_uci::command::{UciCommand, UciGoParams, UciInitPos};
-use cozy_uci::remark::{UciIdInfo, UciInfo, UciOptionInfo, UciRemark, UciScore, UciScoreKind};
-use cozy_uci::UciFormatOptions;
+use cozy_uci::command::{UciClock, UciCommand, UciGoParams, UciInitPos};
+use cozy_uci::remark::{
+    UciBestMove, UciCurrline, UciIdInfo, UciInfo, UciOptionInfo, UciRemark, UciScore, UciScoreKind,
+};
+use cozy_uci::{UciFormatErrorKind, UciFormatOptions, UCI_NULL_MOVE};
 
 fn test_command(cmd_str: &str, expected: UciCommand, options: &mut UciFormatOptions) {
     let cmd = UciCommand::parse_from(cmd_str, options).expect("failed to parse command");
@@ -243,11 +245,11 @@ fn uci_example() {
         }),
         > "stop" => Stop,
         < "bestmove g1f3 ponder d8f6" => BestMove {
-            mv: Move {
+            mv: UciBestMove::Move(Move {
                 from: Square::G1,
                 to: Square::F3,
                 promotion: None,
-            },
+            }),
             ponder: Some(Move {
                 from: Square::D8,
                 to: Square::F6,
@@ -256,3 +258,225 @@ fn uci_example() {
         },
     }
 }
+
+#[test]
+fn info_string_last() {
+    impl_test! {
+        < "info depth 3 string hello  world refutation d1h5" => UciRemark::Info(UciInfo {
+            depth: Some(3),
+            string: Some("hello  world refutation d1h5".to_owned()),
+            ..Default::default()
+        }),
+        < "info refutation d1h5 g6h5 currline 1 e2e4 string tb=3 hash=25" => UciRemark::Info(UciInfo {
+            string: Some("tb=3 hash=25".to_owned()),
+            refutation: Some(vec![
+                Move {
+                    from: Square::D1,
+                    to: Square::H5,
+                    promotion: None,
+                },
+                Move {
+                    from: Square::G6,
+                    to: Square::H5,
+                    promotion: None,
+                },
+            ]),
+            currline: Some(UciCurrline {
+                cpu: Some(1),
+                moves: vec![Move {
+                    from: Square::E2,
+                    to: Square::E4,
+                    promotion: None,
+                }],
+            }),
+            ..Default::default()
+        }),
+    }
+}
+
+#[test]
+fn info_string_pairs() {
+    let mut info = UciInfo {
+        depth: Some(10),
+        ..Default::default()
+    };
+    info.push_string_pair("eval", -35).unwrap();
+    info.push_string_pair("nnue", "nn-ad9b42354671.nnue")
+        .unwrap();
+    assert!(info.push_string_pair("bad key", 1).is_err());
+    assert!(info.push_string_pair("a=b", 1).is_err());
+
+    let options = UciFormatOptions::default();
+    let rmk = UciRemark::Info(info);
+    let rmk_str = rmk.format(&options);
+    assert_eq!(
+        rmk_str,
+        "info depth 10 string eval=-35 nnue=nn-ad9b42354671.nnue"
+    );
+    let UciRemark::Info(info) = UciRemark::parse_from(&rmk_str, &options).unwrap() else {
+        panic!("expected info");
+    };
+    assert_eq!(
+        info.string_pairs().collect::<Vec<_>>(),
+        [("eval", "-35"), ("nnue", "nn-ad9b42354671.nnue")]
+    );
+}
+
+#[test]
+fn null_and_missing_moves() {
+    impl_test! {
+        > "position startpos moves e2e4 0000 d2d4" => UciCommand::Position {
+            init_pos: UciInitPos::StartPos,
+            moves: vec![
+                Move {
+                    from: Square::E2,
+                    to: Square::E4,
+                    promotion: None,
+                },
+                UCI_NULL_MOVE,
+                Move {
+                    from: Square::D2,
+                    to: Square::D4,
+                    promotion: None,
+                },
+            ],
+        },
+        < "info depth 2 pv 0000 e7e5" => UciRemark::Info(UciInfo {
+            depth: Some(2),
+            pv: Some(vec![
+                UCI_NULL_MOVE,
+                Move {
+                    from: Square::E7,
+                    to: Square::E5,
+                    promotion: None,
+                },
+            ]),
+            ..Default::default()
+        }),
+        < "bestmove (none)" => UciRemark::BestMove {
+            mv: UciBestMove::None,
+            ponder: None,
+        },
+        < "bestmove 0000" => UciRemark::BestMove {
+            mv: UciBestMove::Null,
+            ponder: None,
+        },
+        < "bestmove a1a1" => UciRemark::BestMove {
+            mv: UciBestMove::Move(Move {
+                from: Square::A1,
+                to: Square::A1,
+                promotion: None,
+            }),
+            ponder: None,
+        },
+    }
+}
+
+#[test]
+fn lenient_clocks() {
+    impl_test! {
+        > "go wtime -137 btime 5000 winc 0 binc 100" => UciCommand::Go(UciGoParams {
+            wtime: Some(UciClock::from_millis(-137)),
+            btime: Some(UciClock::from_millis(5000)),
+            winc: Some(UciClock::from_millis(0)),
+            binc: Some(UciClock::from_millis(100)),
+            ..Default::default()
+        }),
+        > "go wtime 99999999999999999999999999999999999999999 btime -99999999999999999999999999999999999999999" => UciCommand::Go(UciGoParams {
+            wtime: Some(UciClock::from_millis(i128::MAX)),
+            btime: Some(UciClock::from_millis(i128::MIN)),
+            ..Default::default()
+        }),
+    }
+}
+
+#[test]
+fn clock_to_duration() {
+    assert_eq!(UciClock::from_millis(-137).to_duration(), Duration::ZERO);
+    assert_eq!(
+        UciClock::from_millis(5250).to_duration(),
+        Duration::from_millis(5250)
+    );
+    assert_eq!(
+        UciClock::from_millis(u64::MAX as i128).to_duration(),
+        Duration::from_millis(u64::MAX)
+    );
+    assert_eq!(
+        UciClock::from_millis(i128::MAX).to_duration(),
+        Duration::MAX
+    );
+}
+
+#[test]
+fn multi_word_option_strings() {
+    impl_test! {
+        < "option name Style type combo default Very Solid var Very Solid var Aggressive" => UciRemark::Option {
+            name: "Style".to_owned(),
+            info: UciOptionInfo::Combo {
+                default: "Very Solid".to_owned(),
+                labels: vec!["Very Solid".to_owned(), "Aggressive".to_owned()],
+            },
+        },
+        < "option name Piece type Values type string default 100 300 300 500 900" => UciRemark::Option {
+            name: "Piece type Values".to_owned(),
+            info: UciOptionInfo::String {
+                default: "100 300 300 500 900".to_owned(),
+            },
+        },
+        < "option name Use type check type check default false" => UciRemark::Option {
+            name: "Use type check".to_owned(),
+            info: UciOptionInfo::Check { default: false },
+        },
+    }
+}
+
+#[test]
+fn setoption_name_containing_value() {
+    let mut options = UciFormatOptions::default();
+    let cmd_str = "setoption name Piece value Scale value 120";
+    let expected = UciCommand::SetOption {
+        name: "Piece".to_owned(),
+        value: Some("Scale value 120".to_owned()),
+    };
+    assert_eq!(UciCommand::parse_from(cmd_str, &options).unwrap(), expected);
+
+    let rmk = UciRemark::parse_from(
+        "option name Piece value Scale type spin default 100 min 0 max 200",
+        &options,
+    )
+    .unwrap();
+    options.update_from_remark(&rmk);
+    test_command(
+        cmd_str,
+        UciCommand::SetOption {
+            name: "Piece value Scale".to_owned(),
+            value: Some("120".to_owned()),
+        },
+        &mut options,
+    );
+    test_command(
+        "setoption name Piece value Scale",
+        UciCommand::SetOption {
+            name: "Piece value Scale".to_owned(),
+            value: None,
+        },
+        &mut options,
+    );
+
+    // A name that was not declared still ends at the first `value`.
+    assert_eq!(
+        UciCommand::parse_from("setoption name Pawn value Scale value 90", &options).unwrap(),
+        UciCommand::SetOption {
+            name: "Pawn".to_owned(),
+            value: Some("Scale value 90".to_owned()),
+        }
+    );
+    let undeclared = UciCommand::SetOption {
+        name: "Pawn value Scale".to_owned(),
+        value: Some("90".to_owned()),
+    };
+    assert_eq!(
+        undeclared.try_format(&options).unwrap_err().kind,
+        UciFormatErrorKind::ReservedToken("value")
+    );
+}
@@ -0,0 +1,311 @@
+use std::collections::HashSet;
+use std::io::prelude::*;
+use std::io::{pipe, BufReader};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use cozy_uci::command::UciCommand;
+use cozy_uci::engine::{UciEngine, UciEngineError};
+use cozy_uci::game::{UciGameResult, UciGameSettings};
+use cozy_uci::tournament::*;
+use cozy_uci::UciFormatOptions;
+
+const SCHOLARS_MATE: &[&str] = &["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"];
+
+/// An engine that plays its side of scholar's mate, or disconnects on `go`
+/// if `weak`. Between two strong engines white always wins.
+fn mock(weak: bool) -> UciEngine {
+    let (input, writer) = pipe().unwrap();
+    let (reader, mut output) = pipe().unwrap();
+    thread::spawn(move || {
+        let mut plies = 0;
+        for line in BufReader::new(input).lines() {
+            let line = line.unwrap();
+            let cmd = UciCommand::parse_from(&line, &UciFormatOptions::default()).unwrap();
+            let response = match cmd {
+                UciCommand::Uci => "uciok\n".to_owned(),
+                UciCommand::IsReady => "readyok\n".to_owned(),
+                UciCommand::Position { moves, .. } => {
+                    plies = moves.len();
+                    continue;
+                }
+                UciCommand::Go(_) if weak => return,
+                UciCommand::Go(_) => format!("bestmove {}\n", SCHOLARS_MATE[plies]),
+                _ => continue,
+            };
+            if output.write_all(response.as_bytes()).is_err() {
+                return;
+            }
+        }
+    });
+    UciEngine::from_io(BufReader::new(reader), writer)
+}
+
+fn config(players: usize, format: UciTournamentFormat) -> UciTournamentConfig {
+    UciTournamentConfig {
+        players: (0..players).map(|p| format!("engine-{}", p)).collect(),
+        format,
+        games_per_pairing: 2,
+        settings: UciGameSettings::default(),
+        concurrency: 2,
+    }
+}
+
+/// Runs a tournament where the last player is weak, returning the state
+/// file written and the number of engines spawned.
+fn run(config: &UciTournamentConfig, state: &mut UciTournamentState) -> (String, usize) {
+    let weak = config.players.len() - 1;
+    let spawned = AtomicUsize::new(0);
+    let spawn = |player: usize| -> Result<UciEngine, UciEngineError> {
+        spawned.fetch_add(1, Ordering::SeqCst);
+        Ok(mock(player == weak))
+    };
+    let mut log = UciTournamentState::header(config).into_bytes();
+    let mut seen = Vec::new();
+    run_tournament(config, state, &mut log, &spawn, |record, game| {
+        assert_eq!(record.result, game.result);
+        seen.push(record.pairing.seq);
+    })
+    .unwrap();
+    assert_eq!(seen.len(), HashSet::<usize>::from_iter(seen.clone()).len());
+    (String::from_utf8(log).unwrap(), spawned.into_inner())
+}
+
+#[test]
+fn tournament_schedules() {
+    let games = schedule(4, UciTournamentFormat::RoundRobin, 2);
+    assert_eq!(games.len(), 12);
+    assert!(games.iter().enumerate().all(|(i, g)| g.seq == i));
+    for round in 1..=3 {
+        let players: Vec<usize> = games
+            .iter()
+            .filter(|g| g.round == round)
+            .flat_map(|g| [g.white, g.black])
+            .collect();
+        assert_eq!(players.len(), 8);
+        assert_eq!(HashSet::<usize>::from_iter(players).len(), 4);
+    }
+    let pairs: HashSet<(usize, usize)> = games
+        .iter()
+        .map(|g| (g.white.min(g.black), g.white.max(g.black)))
+        .collect();
+    assert_eq!(pairs.len(), 6);
+    assert_eq!(
+        (games[0].white, games[0].black),
+        (games[1].black, games[1].white)
+    );
+
+    let games = schedule(3, UciTournamentFormat::RoundRobin, 1);
+    assert_eq!(games.len(), 3);
+    assert_eq!(
+        HashSet::<u32>::from_iter(games.iter().map(|g| g.round)).len(),
+        3
+    );
+
+    // With one game per pairing, colours alternate as in Berger tables:
+    // nobody has more than one extra white or black, or three in a row.
+    for players in 2..=12 {
+        let games = schedule(players, UciTournamentFormat::RoundRobin, 1);
+        assert_eq!(games.len(), players * (players - 1) / 2);
+        for player in 0..players {
+            let colours: Vec<i32> = games
+                .iter()
+                .filter_map(|g| match player {
+                    p if p == g.white => Some(1),
+                    p if p == g.black => Some(-1),
+                    _ => None,
+                })
+                .collect();
+            let balance: i32 = colours.iter().sum();
+            assert!(balance.abs() <= 1, "player {} of {}", player, players);
+            assert!(
+                colours.windows(3).all(|w| w[0] != w[1] || w[1] != w[2]),
+                "player {} of {}",
+                player,
+                players
+            );
+        }
+    }
+
+    let games = schedule(4, UciTournamentFormat::Gauntlet, 2);
+    assert_eq!(games.len(), 6);
+    assert!(games.iter().all(|g| g.white == 0 || g.black == 0));
+    let games = schedule(5, UciTournamentFormat::Gauntlet, 1);
+    assert_eq!(games.iter().filter(|g| g.white == 0).count(), 2);
+
+    assert_eq!(
+        "swiss:5".parse::<UciTournamentFormat>().unwrap(),
+        UciTournamentFormat::Swiss { rounds: 5 }
+    );
+    assert_eq!(
+        "gauntlet".parse::<UciTournamentFormat>().unwrap(),
+        UciTournamentFormat::Gauntlet
+    );
+    assert!("swiss".parse::<UciTournamentFormat>().is_err());
+    assert_eq!(
+        UciTournamentFormat::Swiss { rounds: 5 }.to_string(),
+        "swiss:5"
+    );
+}
+
+#[test]
+fn tournament_swiss_pairing() {
+    let mut state = UciTournamentState::default();
+    let (pairs, bye) = swiss_round(5, &state, 1);
+    assert_eq!(pairs, [(0, 1), (2, 3)]);
+    assert_eq!(bye, Some(4));
+
+    let game = |seq, white, black, result| UciGameRecord {
+        pairing: UciPairing {
+            seq,
+            round: 1,
+            white,
+            black,
+        },
+        result,
+    };
+    state.games = vec![
+        game(0, 0, 1, UciGameResult::BlackWins),
+        game(1, 2, 3, UciGameResult::Draw),
+    ];
+    state.byes = vec![(1, 4)];
+    let (pairs, bye) = swiss_round(5, &state, 2);
+    // 1 and 4 lead, and 0 is the lowest ranked player without a bye.
+    assert_eq!(bye, Some(0));
+    // 1 and 4 meeting would force the rematch 2 vs 3, and 2 has had white.
+    assert_eq!(pairs, [(1, 2), (4, 3)]);
+
+    // The three lowest ranked players have lost to everyone else, so a
+    // rematch cannot be avoided, which takes very long to find out by
+    // backtracking through the pairings of the others.
+    let mut state = UciTournamentState::default();
+    for a in 21..24 {
+        for b in 0..21 {
+            let seq = state.games.len();
+            state.games.push(game(seq, b, a, UciGameResult::WhiteWins));
+        }
+    }
+    let (pairs, bye) = swiss_round(24, &state, 2);
+    assert_eq!(bye, None);
+    let paired: HashSet<usize> = pairs.iter().flat_map(|&(a, b)| [a, b]).collect();
+    assert_eq!(paired.len(), 24);
+}
+
+#[test]
+fn tournament_state_file() {
+    let config = config(3, UciTournamentFormat::Swiss { rounds: 2 });
+    let file = "format swiss:2\ngames-per-pairing 2\nplayer engine-0\nplayer engine-1\n\
+                player engine-2\ngame 0 1 0 1 1-0\nbye 1 2\ngame 1 1 1 0 1/2-1/2\n";
+    assert_eq!(
+        UciTournamentState::header(&config),
+        file[..file.find("game ").unwrap()]
+    );
+    let state = UciTournamentState::parse(file, &config).unwrap();
+    assert_eq!(state.games.len(), 2);
+    assert_eq!(state.games[1].pairing.white, 1);
+    assert_eq!(state.games[1].result, UciGameResult::Draw);
+    assert_eq!(state.byes, [(1, 2)]);
+    assert_eq!(state.points(3), [1.5, 0.5, 1.0]);
+
+    let other = self::config(3, UciTournamentFormat::RoundRobin);
+    assert!(matches!(
+        UciTournamentState::parse(file, &other),
+        Err(UciTournamentError::Mismatch(_))
+    ));
+    let bad = file.replace("game 1 1 1 0", "game 1 1 1 3");
+    assert!(matches!(
+        UciTournamentState::parse(&bad, &config),
+        Err(UciTournamentError::State { line: 8, .. })
+    ));
+}
+
+#[test]
+fn tournament_run_and_resume() {
+    let config = config(3, UciTournamentFormat::RoundRobin);
+    let mut state = UciTournamentState::default();
+    let (log, spawned) = run(&config, &mut state);
+    assert_eq!(state.games.len(), 6);
+    assert!(spawned >= 3);
+    assert_eq!(
+        UciTournamentState::parse(&log, &config)
+            .unwrap()
+            .games
+            .len(),
+        6
+    );
+    // The strong players split their games and beat the weak player.
+    assert_eq!(state.points(3), [3.0, 3.0, 0.0]);
+
+    let (_, spawned) = run(&config, &mut state);
+    assert_eq!(spawned, 0);
+    assert_eq!(state.games.len(), 6);
+
+    let partial: String = log.lines().take(7).map(|l| l.to_owned() + "\n").collect();
+    let mut resumed = UciTournamentState::parse(&partial, &config).unwrap();
+    assert_eq!(resumed.games.len(), 2);
+    let (log, _) = run(&config, &mut resumed);
+    assert_eq!(log.lines().filter(|l| l.starts_with("game ")).count(), 4);
+    let seqs: HashSet<usize> = resumed.games.iter().map(|g| g.pairing.seq).collect();
+    assert_eq!(seqs, (0..6).collect());
+    assert_eq!(resumed.points(3), [3.0, 3.0, 0.0]);
+}
+
+#[test]
+fn tournament_swiss_run() {
+    let config = config(3, UciTournamentFormat::Swiss { rounds: 3 });
+    let mut state = UciTournamentState::default();
+    let (log, _) = run(&config, &mut state);
+    assert_eq!(state.games.len(), 6);
+    assert_eq!(state.byes.len(), 3);
+    let byes: HashSet<usize> = state.byes.iter().map(|&(_, p)| p).collect();
+    assert_eq!(byes.len(), 3);
+    assert_eq!(UciTournamentState::parse(&log, &config).unwrap(), state);
+
+    let (log, spawned) = run(&config, &mut state);
+    assert_eq!(spawned, 0);
+    assert_eq!(log, UciTournamentState::header(&config));
+}
+
+#[test]
+fn tournament_spawn_failure() {
+    let config = config(2, UciTournamentFormat::Gauntlet);
+    let mut state = UciTournamentState::default();
+    let spawn = |player: usize| match player {
+        0 => Ok(mock(false)),
+        _ => Err(UciEngineError::Disconnected),
+    };
+    let mut log = Vec::new();
+    run_tournament(&config, &mut state, &mut log, &spawn, |_, _| {}).unwrap();
+    assert_eq!(state.points(2), [2.0, 0.0]);
+}
+
+#[test]
+fn tournament_crosstable() {
+    let even = UciElo::from_results(1, 0, 1).unwrap();
+    assert!(even.elo.abs() < 1e-9);
+    let ahead = UciElo::from_results(30, 0, 10).unwrap();
+    assert!((ahead.elo - 190.85).abs() < 0.01);
+    assert!((ahead.error - 135.58).abs() < 0.01, "{}", ahead.error);
+    assert_eq!(UciElo::from_results(3, 0, 1).unwrap().error, f64::INFINITY);
+    let wider = UciElo::from_results(3, 2, 1).unwrap();
+    let narrower = UciElo::from_results(30, 20, 10).unwrap();
+    assert!(narrower.error < wider.error);
+    assert_eq!(UciElo::from_results(0, 0, 0), None);
+
+    let config = config(3, UciTournamentFormat::RoundRobin);
+    let mut state = UciTournamentState::default();
+    run(&config, &mut state);
+    let table = UciCrosstable::new(&config.players, &state);
+    let order: Vec<usize> = table.rows.iter().map(|r| r.player).collect();
+    assert_eq!(order, [0, 1, 2]);
+    assert_eq!(table.rows[0].against[1], (1.0, 2));
+    assert_eq!(table.rows[0].against[2], (2.0, 2));
+    assert_eq!(table.rows[2].elo.unwrap().elo, f64::NEG_INFINITY);
+    let text = table.to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("Rank Name"));
+    assert!(lines[1].contains("engine-0"));
+    assert!(lines[1].ends_with("x     1/2     2/2"));
+    assert!(lines[3].contains("-inf"));
+}
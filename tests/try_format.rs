@@ -0,0 +1,191 @@
+use cozy_chess::*;
+use cozy_uci::command::{UciCommand, UciInitPos};
+use cozy_uci::extensions::UciExtCommand;
+use cozy_uci::remark::{
+    UciBestMove, UciIdInfo, UciInfo, UciOptionInfo, UciRemark, UciScore, UciScoreKind,
+};
+use cozy_uci::{UciFormatError, UciFormatErrorKind, UciFormatOptions, UciParseErrorKind};
+
+fn command_error(cmd: UciCommand) -> UciFormatError {
+    cmd.try_format(&UciFormatOptions::default())
+        .expect_err("command should not be representable")
+}
+
+fn remark_error(rmk: UciRemark) -> UciFormatError {
+    rmk.try_format(&UciFormatOptions::default())
+        .expect_err("remark should not be representable")
+}
+
+#[test]
+fn setoption_value_injection() {
+    let err = command_error(UciCommand::SetOption {
+        name: "NalimovPath".to_owned(),
+        value: Some("x\nquit".to_owned()),
+    });
+    assert_eq!(err.field, "value");
+    assert_eq!(err.kind, UciFormatErrorKind::LineBreak);
+
+    let options = UciFormatOptions::default();
+    let err = UciCommand::parse_from("setoption name NalimovPath value x\rquit", &options)
+        .expect_err("line break should not parse");
+    assert!(matches!(err.kind, UciParseErrorKind::LineBreakInString));
+}
+
+#[test]
+fn reserved_tokens() {
+    let err = command_error(UciCommand::SetOption {
+        name: "Contempt value".to_owned(),
+        value: Some("10".to_owned()),
+    });
+    assert_eq!(err.kind, UciFormatErrorKind::ReservedToken("value"));
+
+    let err = remark_error(UciRemark::Option {
+        name: "Mode type string default fast".to_owned(),
+        info: UciOptionInfo::Button,
+    });
+    assert_eq!(err.kind, UciFormatErrorKind::ReservedToken("type"));
+
+    let err = remark_error(UciRemark::Option {
+        name: "Style".to_owned(),
+        info: UciOptionInfo::Combo {
+            default: "Solid".to_owned(),
+            labels: vec!["Solid".to_owned(), "Solid var Risky".to_owned()],
+        },
+    });
+    assert_eq!(err.field, "var");
+    assert_eq!(err.kind, UciFormatErrorKind::ReservedToken("var"));
+
+    // Only a `type` that the rest of the line reads after is ambiguous.
+    let rmk = UciRemark::Option {
+        name: "Eval type".to_owned(),
+        info: UciOptionInfo::Button,
+    };
+    let options = UciFormatOptions::default();
+    assert_eq!(
+        rmk.try_format(&options).unwrap(),
+        "option name Eval type type button"
+    );
+
+    let err = remark_error(UciRemark::Option {
+        name: " Hash".to_owned(),
+        info: UciOptionInfo::Button,
+    });
+    assert_eq!(err.kind, UciFormatErrorKind::SurroundingWhitespace);
+}
+
+#[test]
+fn declared_option_names() {
+    let options = UciFormatOptions {
+        option_names: vec!["Contempt".to_owned(), "Contempt value White".to_owned()],
+        ..Default::default()
+    };
+    let cmd = UciCommand::SetOption {
+        name: "Contempt value White".to_owned(),
+        value: Some("10".to_owned()),
+    };
+    let err = cmd.try_format(&options).unwrap_err();
+    assert_eq!(
+        err.kind,
+        UciFormatErrorKind::DeclaredOptionName("Contempt".to_owned())
+    );
+
+    let cmd = UciCommand::SetOption {
+        name: "Contempt".to_owned(),
+        value: Some("White".to_owned()),
+    };
+    let line = cmd.try_format(&options).unwrap();
+    assert_eq!(UciCommand::parse_from(&line, &options).unwrap(), cmd);
+
+    let options = UciFormatOptions {
+        option_names: vec!["Contempt value White".to_owned()],
+        ..Default::default()
+    };
+    let cmd = UciCommand::SetOption {
+        name: "Contempt value White".to_owned(),
+        value: Some("10".to_owned()),
+    };
+    let line = cmd.try_format(&options).unwrap();
+    assert_eq!(UciCommand::parse_from(&line, &options).unwrap(), cmd);
+}
+
+#[test]
+fn unrepresentable_strings() {
+    let err = remark_error(UciRemark::Id(UciIdInfo::Name("Engine ".to_owned())));
+    assert_eq!(err.kind, UciFormatErrorKind::SurroundingWhitespace);
+}
+
+#[test]
+fn unrepresentable_moves_and_positions() {
+    let mv = Move {
+        from: Square::E7,
+        to: Square::E8,
+        promotion: Some(Piece::King),
+    };
+    let err = remark_error(UciRemark::BestMove {
+        mv: UciBestMove::Move(mv),
+        ponder: None,
+    });
+    assert_eq!(err.kind, UciFormatErrorKind::InvalidPromotion(mv));
+
+    let cmd = UciCommand::Position {
+        init_pos: UciInitPos::Board(Board::chess960_startpos(0)),
+        moves: Vec::new(),
+    };
+    let err = command_error(cmd.clone());
+    assert_eq!(err.kind, UciFormatErrorKind::UnrepresentableBoard);
+    let options = UciFormatOptions {
+        chess960: true,
+        ..Default::default()
+    };
+    assert!(cmd.try_format(&options).is_ok());
+}
+
+#[test]
+fn wdl_requires_option() {
+    let rmk = UciRemark::Info(UciInfo {
+        score: Some(UciScore {
+            cp: Some(20),
+            mate: None,
+            wdl: Some((100, 800, 100)),
+            kind: UciScoreKind::Exact,
+        }),
+        ..Default::default()
+    });
+    assert_eq!(
+        remark_error(rmk.clone()).kind,
+        UciFormatErrorKind::WdlDisabled
+    );
+    let options = UciFormatOptions {
+        wdl: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        rmk.try_format(&options).unwrap(),
+        "info score cp 20 wdl 100 800 100"
+    );
+    assert_eq!(rmk.format(&UciFormatOptions::default()), "info score cp 20");
+}
+
+#[test]
+fn format_is_lossy() {
+    let options = UciFormatOptions::default();
+    let rmk = UciRemark::Id(UciIdInfo::Name("Fruit 2.1 ".to_owned()));
+    assert_eq!(rmk.format(&options), "id name Fruit 2.1 ");
+    let cmd = UciCommand::SetOption {
+        name: "NalimovPath".to_owned(),
+        value: Some("x\nquit".to_owned()),
+    };
+    assert_eq!(
+        cmd.format(&options),
+        "setoption name NalimovPath value x quit"
+    );
+}
+
+/// Extension messages exist and format without the `extensions` feature,
+/// which only adds their parsers.
+#[test]
+fn extensions_format_without_feature() {
+    let options = UciFormatOptions::default();
+    let cmd = UciCommand::Ext(UciExtCommand::ExportNet(Some("net.nnue".to_owned())));
+    assert_eq!(cmd.try_format(&options).unwrap(), "export_net net.nnue");
+}
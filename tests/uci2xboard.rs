@@ -0,0 +1,287 @@
+use std::io::prelude::*;
+use std::io::{pipe, BufReader};
+use std::thread;
+
+use cozy_uci::cecp::*;
+use cozy_uci::remark::{UciOptionInfo, UciScoreValue};
+use cozy_uci::uci2xboard::*;
+use cozy_uci::UciFormatOptions;
+
+fn to_engine(out: &Uci2XboardOutput) -> Vec<String> {
+    let options = UciFormatOptions::default();
+    out.to_engine
+        .iter()
+        .map(|cmd| cmd.format(&options))
+        .collect()
+}
+
+fn handshake(adapter: &mut Uci2Xboard) {
+    let out = adapter.start();
+    assert_eq!(to_engine(&out), ["uci"]);
+    adapter.gui_line("xboard");
+    let out = adapter.gui_line("protover 2");
+    assert_eq!(out.to_gui, ["feature done=0"]);
+    for line in [
+        "id name Mock 1.0",
+        "option name Hash type spin default 16 min 1 max 1024",
+        "option name Ponder type check default false",
+        "option name Style type combo default Normal var Solid var Normal var Risky",
+        "option name UCI_Chess960 type check default false",
+    ] {
+        assert_eq!(adapter.engine_line(line), Uci2XboardOutput::default());
+    }
+}
+
+#[test]
+fn cecp_scores() {
+    assert_eq!(cecp_score(UciScoreValue::Cp(-35)), -35);
+    assert_eq!(cecp_score(UciScoreValue::Cp(i32::MAX)), CECP_MATE_SCORE - 1);
+    assert_eq!(cecp_score(UciScoreValue::Mate(3)), 100003);
+    assert_eq!(cecp_score(UciScoreValue::Mate(-2)), -100002);
+
+    let option = UciOptionInfo::Combo {
+        default: "B".to_owned(),
+        labels: vec!["A".to_owned(), "B".to_owned()],
+    };
+    assert_eq!(
+        cecp_feature_option("Style", &option),
+        "Style -combo A /// *B"
+    );
+    let option = UciOptionInfo::Check { default: true };
+    assert_eq!(cecp_feature_option("Ponder", &option), "Ponder -check 1");
+}
+
+#[test]
+fn uci2xboard_features() {
+    let mut adapter = Uci2Xboard::new();
+    handshake(&mut adapter);
+    let out = adapter.engine_line("uciok");
+    assert!(out.to_engine.is_empty());
+    assert_eq!(
+        out.to_gui,
+        [
+            "feature myname=\"Mock 1.0\" setboard=1 usermove=1 ping=1 analyze=1 playother=1 \
+             colors=0 san=0 sigint=0 sigterm=0 reuse=1 variants=\"normal,fischerandom\" memory=1",
+            "feature option=\"Ponder -check 0\"",
+            "feature option=\"Style -combo Solid /// *Normal /// Risky\"",
+            "feature done=1",
+        ]
+    );
+
+    let out = adapter.gui_line("option Ponder=1");
+    assert_eq!(to_engine(&out), ["setoption name Ponder value true"]);
+    let out = adapter.gui_line("memory 64");
+    assert_eq!(to_engine(&out), ["setoption name Hash value 64"]);
+    let out = adapter.gui_line("option Hash= 32 ");
+    assert_eq!(to_engine(&out), ["setoption name Hash value 32"]);
+    let out = adapter.gui_line("option Missing=3");
+    assert_eq!(out.to_gui, ["Error (unknown option): Missing"]);
+    let out = adapter.gui_line("frobnicate");
+    assert_eq!(out.to_gui, ["Error (unknown command): frobnicate"]);
+}
+
+#[test]
+fn uci2xboard_game() {
+    let mut adapter = Uci2Xboard::new();
+    handshake(&mut adapter);
+    adapter.engine_line("uciok");
+
+    let out = adapter.gui_line("new");
+    assert_eq!(to_engine(&out), ["ucinewgame"]);
+    adapter.gui_line("level 40 5 0");
+    adapter.gui_line("post");
+    adapter.gui_line("time 30000");
+    adapter.gui_line("otim 29000");
+    let out = adapter.gui_line("usermove e2e4");
+    assert_eq!(
+        to_engine(&out),
+        [
+            "position startpos moves e2e4",
+            "go wtime 290000 btime 300000 winc 0 binc 0 movestogo 40",
+        ]
+    );
+    let out = adapter.gui_line("usermove e7e4");
+    assert_eq!(out.to_gui, ["Illegal move: e7e4"]);
+
+    let out = adapter.engine_line("info depth 5 score cp -20 time 120 nodes 5000 pv e7e5 g1f3");
+    assert_eq!(out.to_gui, ["5 -20 12 5000 1... e5 2. Nf3"]);
+    let out = adapter.engine_line("bestmove e7e5 ponder g1f3");
+    assert_eq!(out.to_gui, ["move e7e5"]);
+
+    adapter.gui_line("st 2");
+    adapter.gui_line("sd 8");
+    let out = adapter.gui_line("g1f3");
+    assert_eq!(
+        to_engine(&out),
+        [
+            "position startpos moves e2e4 e7e5 g1f3",
+            "go depth 8 movetime 2000",
+        ]
+    );
+    // A search interrupted by the GUI does not produce a move.
+    let out = adapter.gui_line("force");
+    assert_eq!(to_engine(&out), ["stop"]);
+    let out = adapter.engine_line("bestmove b8c6");
+    assert!(out.to_gui.is_empty());
+
+    let out = adapter.gui_line("go");
+    assert_eq!(
+        to_engine(&out),
+        [
+            "position startpos moves e2e4 e7e5 g1f3",
+            "go depth 8 movetime 2000",
+        ]
+    );
+    let out = adapter.gui_line("?");
+    assert_eq!(to_engine(&out), ["stop"]);
+    let out = adapter.engine_line("bestmove b8c6");
+    assert_eq!(out.to_gui, ["move b8c6"]);
+
+    let out = adapter.gui_line("ping 7");
+    assert_eq!(to_engine(&out), ["isready"]);
+    let out = adapter.engine_line("readyok");
+    assert_eq!(out.to_gui, ["pong 7"]);
+
+    let out = adapter.gui_line("quit");
+    assert_eq!(to_engine(&out), ["quit"]);
+    assert!(adapter.is_done());
+}
+
+#[test]
+fn uci2xboard_movestogo_after_setboard() {
+    let mut adapter = Uci2Xboard::new();
+    handshake(&mut adapter);
+    adapter.engine_line("uciok");
+
+    adapter.gui_line("new");
+    adapter.gui_line("level 40 5 0");
+    adapter.gui_line("force");
+    let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 30";
+    adapter.gui_line(&format!("setboard {}", fen));
+    adapter.gui_line("usermove f1c4");
+    let out = adapter.gui_line("go");
+    assert_eq!(
+        to_engine(&out)[1],
+        "go wtime 300000 btime 300000 winc 0 binc 0 movestogo 40"
+    );
+    let out = adapter.engine_line("bestmove g8f6");
+    assert_eq!(out.to_gui, ["move g8f6"]);
+
+    let out = adapter.gui_line("usermove d2d3");
+    assert_eq!(
+        to_engine(&out)[1],
+        "go wtime 300000 btime 300000 winc 0 binc 0 movestogo 39"
+    );
+}
+
+#[test]
+fn uci2xboard_analyze() {
+    let mut adapter = Uci2Xboard::new();
+    handshake(&mut adapter);
+    adapter.engine_line("uciok");
+    adapter.gui_line("new");
+    adapter.gui_line("force");
+    adapter.gui_line("setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+
+    let out = adapter.gui_line("analyze");
+    assert_eq!(
+        to_engine(&out),
+        [
+            "position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "go infinite"
+        ]
+    );
+    // Thinking is shown while analysing even without `post`.
+    let out = adapter.engine_line("info depth 3 score mate 1 time 5 nodes 200 pv a1a8");
+    assert_eq!(out.to_gui, ["3 100001 0 200 1. Ra8#"]);
+
+    let out = adapter.gui_line("usermove a1a8");
+    assert_eq!(
+        to_engine(&out),
+        [
+            "stop",
+            "position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1 moves a1a8",
+            "go infinite"
+        ]
+    );
+    let out = adapter.engine_line("bestmove a1a8");
+    assert!(out.to_gui.is_empty());
+
+    let out = adapter.gui_line("undo");
+    assert_eq!(
+        to_engine(&out),
+        [
+            "stop",
+            "position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "go infinite"
+        ]
+    );
+    let out = adapter.gui_line("exit");
+    assert_eq!(to_engine(&out), ["stop"]);
+    assert!(adapter.engine_line("bestmove a1a8").to_gui.is_empty());
+}
+
+#[test]
+fn uci2xboard_pipes() {
+    let (gui_reader, mut gui_input) = pipe().unwrap();
+    let (gui_output, gui_writer) = pipe().unwrap();
+    let (engine_input, engine_writer) = pipe().unwrap();
+    let (engine_reader, mut engine_output) = pipe().unwrap();
+
+    let engine = thread::spawn(move || {
+        let mut commands = Vec::new();
+        for line in BufReader::new(engine_input).lines() {
+            let line = line.unwrap();
+            let reply = match line.as_str() {
+                "uci" => "id name Pipe\noption name Book type string default none\nuciok\n",
+                "isready" => "readyok\n",
+                _ if line.starts_with("go") => "bestmove e7e5\n",
+                _ => "",
+            };
+            engine_output.write_all(reply.as_bytes()).unwrap();
+            commands.push(line);
+        }
+        commands
+    });
+    let adapter = thread::spawn(move || {
+        run_uci2xboard(
+            Uci2Xboard::new(),
+            BufReader::new(gui_reader),
+            gui_writer,
+            BufReader::new(engine_reader),
+            engine_writer,
+        )
+    });
+
+    let mut gui_output = BufReader::new(gui_output).lines().map(Result::unwrap);
+    writeln!(gui_input, "xboard\nprotover 2").unwrap();
+    let features: Vec<String> = gui_output
+        .by_ref()
+        .take_while(|line| line != "feature done=1")
+        .collect();
+    assert!(features
+        .iter()
+        .any(|line| line.starts_with("feature myname=\"Pipe\"")));
+    // A value that cannot be sent to the engine is reported to the GUI.
+    writeln!(gui_input, "option Book=a\rb").unwrap();
+    assert_eq!(
+        gui_output.next().unwrap(),
+        "Error (cannot format value: string contains a line break): \
+         setoption name Book value a b"
+    );
+    writeln!(gui_input, "new\nusermove e2e4").unwrap();
+    assert_eq!(gui_output.next().unwrap(), "move e7e5");
+    writeln!(gui_input, "quit").unwrap();
+    adapter.join().unwrap().unwrap();
+
+    assert_eq!(
+        engine.join().unwrap(),
+        [
+            "uci",
+            "ucinewgame",
+            "position startpos moves e2e4",
+            "go wtime 300000 btime 300000 winc 0 binc 0 movestogo 40",
+            "quit",
+        ]
+    );
+}
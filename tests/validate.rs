@@ -0,0 +1,99 @@
+mod common;
+
+use cozy_chess::*;
+use cozy_uci::command::{UciCommand, UciInitPos};
+use cozy_uci::message::UciMessage;
+use cozy_uci::remark::UciRemark;
+use cozy_uci::validate::{UciIllegalMoveError, UciMoveValidator};
+use cozy_uci::{UciFormatOptions, UCI_NULL_MOVE};
+
+fn validate_logs(log_path: &str) {
+    let mut validator = None;
+    common::replay_log(log_path, |msg, options| match msg {
+        UciMessage::Command(UciCommand::Position { init_pos, moves }) => {
+            let v = UciMoveValidator::new(&init_pos, &moves, options)
+                .expect("illegal move in position");
+            validator = Some(v);
+        }
+        UciMessage::Remark(rmk) => {
+            if let Some(validator) = &validator {
+                validator
+                    .validate_remark(&rmk)
+                    .expect("illegal move in remark");
+            }
+        }
+        _ => {}
+    });
+}
+
+#[test]
+fn validate_sf_b_game_1() {
+    validate_logs("tests/uci_logs/sf_b_game_1.txt");
+}
+
+#[test]
+fn validate_berserk_b_game_1() {
+    validate_logs("tests/uci_logs/berserk_b_game_1.txt");
+}
+
+#[test]
+fn validate_standard_castling() {
+    let options = UciFormatOptions::default();
+    let moves = ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"].map(|mv| mv.parse().unwrap());
+    let validator = UciMoveValidator::new(&UciInitPos::StartPos, &moves, &options).unwrap();
+    let rmk = UciRemark::parse_from("bestmove e1g1 ponder f8c5", &options).unwrap();
+    validator.validate_remark(&rmk).unwrap();
+}
+
+#[test]
+fn validate_reports_first_illegal_move() {
+    let options = UciFormatOptions::default();
+    let validator = UciMoveValidator::new(&UciInitPos::StartPos, &[], &options).unwrap();
+    let rmk = UciRemark::parse_from("info depth 3 pv e2e4 e7e5 e4e5", &options).unwrap();
+    let mut board = Board::default();
+    board.play("e2e4".parse().unwrap());
+    board.play("e7e5".parse().unwrap());
+    assert_eq!(
+        validator.validate_remark(&rmk),
+        Err(UciIllegalMoveError {
+            field: "pv",
+            ply: 2,
+            mv: Some("e4e5".parse().unwrap()),
+            board: Box::new(board),
+        })
+    );
+
+    let rmk = UciRemark::parse_from("bestmove e2e5", &options).unwrap();
+    assert_eq!(validator.validate_remark(&rmk).unwrap_err().ply, 0);
+}
+
+#[test]
+fn validate_null_move() {
+    let options = UciFormatOptions::default();
+    let moves = ["e2e4", "0000", "d2d4"].map(|mv| match mv {
+        "0000" => UCI_NULL_MOVE,
+        mv => mv.parse().unwrap(),
+    });
+    let validator = UciMoveValidator::new(&UciInitPos::StartPos, &moves, &options).unwrap();
+    let rmk = UciRemark::parse_from("info depth 2 pv 0000 e2e3", &options).unwrap();
+    assert_eq!(validator.validate_remark(&rmk).unwrap_err().ply, 1);
+    let rmk = UciRemark::parse_from("info depth 2 pv 0000 e1e2", &options).unwrap();
+    validator.validate_remark(&rmk).unwrap();
+    let rmk = UciRemark::parse_from("info depth 2 pv a1a1 e1e2", &options).unwrap();
+    assert_eq!(validator.validate_remark(&rmk).unwrap_err().ply, 0);
+    for rmk in ["bestmove (none)", "bestmove 0000"] {
+        let rmk = UciRemark::parse_from(rmk, &options).unwrap();
+        let error = validator.validate_remark(&rmk).unwrap_err();
+        assert_eq!((error.field, error.ply, error.mv), ("bestmove", 0, None));
+        assert!(error
+            .to_string()
+            .starts_with("engine returned no move in bestmove with legal moves available"));
+    }
+
+    let mate = UciInitPos::Board("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1".parse().unwrap());
+    let validator = UciMoveValidator::new(&mate, &[], &options).unwrap();
+    for rmk in ["bestmove (none)", "bestmove 0000"] {
+        let rmk = UciRemark::parse_from(rmk, &options).unwrap();
+        validator.validate_remark(&rmk).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use cozy_chess::*;
+use cozy_uci::remark::{UciScore, UciScoreKind, UciScoreValue};
+use cozy_uci::wdl::*;
+
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn samples(model: &impl WdlModel, count: usize) -> Vec<WdlSample> {
+    let mut rng = Xorshift(0x2545f4914f6cdd1d);
+    (0..count)
+        .map(|_| {
+            let cp = (rng.next() % 801) as i32 - 400;
+            let material = 17 + (rng.next() % 62) as u32;
+            let wdl = model.cp_wdl(cp as f64, material);
+            let roll = rng.unit();
+            let outcome = if roll < wdl.win {
+                WdlOutcome::Win
+            } else if roll < wdl.win + wdl.draw {
+                WdlOutcome::Draw
+            } else {
+                WdlOutcome::Loss
+            };
+            WdlSample {
+                cp,
+                material,
+                outcome,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn logistic_model() {
+    let model = LogisticWdlModel::default();
+    let even = model.cp_wdl(0.0, 0);
+    assert!(even.draw > 0.9);
+    assert!((even.win - even.loss).abs() < 1e-12);
+    assert!((model.cp_wdl(100.0, 0).win - 0.5).abs() < 1e-12);
+    assert_eq!(model.value_wdl(UciScoreValue::Mate(3), 0), Wdl::WIN);
+    assert_eq!(model.value_wdl(UciScoreValue::Mate(-3), 0), Wdl::LOSS);
+}
+
+#[test]
+fn score_roundtrip() {
+    let board = Board::default();
+    let model = MaterialWdlModel::default();
+    for cp in [-250, -40, 0, 15, 120] {
+        let score = UciScore::from(UciScoreValue::Cp(cp));
+        let wdl = model.score_wdl(&score, &board).unwrap();
+        let roundtripped = model.wdl_score(&wdl, &board);
+        assert_eq!(roundtripped.cp, Some(cp));
+        assert_eq!(roundtripped.wdl, Some(wdl.to_permill()));
+    }
+
+    let engine_wdl = UciScore {
+        cp: Some(30),
+        mate: None,
+        wdl: Some((100, 850, 50)),
+        kind: UciScoreKind::Exact,
+    };
+    assert_eq!(model.expected_score(&engine_wdl, &board), Some(0.525));
+}
+
+#[test]
+fn permill_conversion() {
+    let wdl = Wdl {
+        win: 0.3336,
+        draw: 0.3336,
+        loss: 0.3328,
+    };
+    assert_eq!(wdl.to_permill(), (334, 333, 333));
+    assert_eq!(Wdl::from_permill((0, 0, 0)), None);
+    assert_eq!(
+        Wdl::from_permill((0, 1000, 0)).unwrap().expected_score(),
+        0.5
+    );
+}
+
+#[test]
+fn fit_logistic_model() {
+    let model = LogisticWdlModel { a: 150.0, b: 60.0 };
+    let fitted = fit_logistic(&samples(&model, 20_000)).unwrap();
+    assert!((fitted.a - model.a).abs() < 5.0, "{:?}", fitted);
+    assert!((fitted.b - model.b).abs() < 5.0, "{:?}", fitted);
+}
+
+#[test]
+fn fit_material_model() {
+    let model = MaterialWdlModel {
+        a: [0.0, -60.0, 100.0, 120.0],
+        b: [0.0, 0.0, 30.0, 30.0],
+    };
+    let fitted = fit_material(&samples(&model, 100_000)).unwrap();
+    for material in [20, 40, 58, 76] {
+        let (a, b) = fitted.params(material);
+        let (expected_a, expected_b) = model.params(material);
+        assert!((a - expected_a).abs() < 10.0, "{:?}", fitted);
+        assert!((b - expected_b).abs() < 10.0, "{:?}", fitted);
+    }
+    assert_eq!(fit_material(&samples(&model, 100)), None);
+}
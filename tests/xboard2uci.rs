@@ -0,0 +1,339 @@
+use std::io::prelude::*;
+use std::io::{pipe, BufReader};
+use std::thread;
+use std::time::Duration;
+
+use cozy_chess::Board;
+use cozy_uci::cecp::*;
+use cozy_uci::command::UciCommand;
+use cozy_uci::remark::{UciOptionInfo, UciRemark, UciScoreValue};
+use cozy_uci::xboard2uci::*;
+use cozy_uci::UciFormatOptions;
+
+fn send(adapter: &mut Xboard2Uci, line: &str) -> Xboard2UciOutput {
+    let cmd = UciCommand::parse_from(line, &UciFormatOptions::default()).unwrap();
+    adapter.command(&cmd)
+}
+
+fn to_gui(out: &Xboard2UciOutput) -> Vec<String> {
+    let options = UciFormatOptions::default();
+    out.to_gui.iter().map(|rmk| rmk.format(&options)).collect()
+}
+
+fn handshake() -> Xboard2Uci {
+    let mut adapter = Xboard2Uci::new();
+    assert_eq!(adapter.start().to_engine, ["xboard", "protover 2"]);
+    assert_eq!(send(&mut adapter, "uci"), Xboard2UciOutput::default());
+    let out = adapter.engine_line(
+        "feature myname=\"Old Engine 2.1\" usermove=1 ping=1 san=1 setboard=1 memory=1",
+    );
+    assert_eq!(
+        out.to_engine,
+        [
+            "accepted myname",
+            "accepted usermove",
+            "accepted ping",
+            "rejected san",
+            "accepted setboard",
+            "accepted memory",
+        ]
+    );
+    adapter.engine_line("feature option=\"Resign Threshold -spin 800 100 5000\"");
+    adapter.engine_line("feature option=\"Book -check 1\"");
+    let out = adapter.engine_line("feature done=1");
+    assert_eq!(out.to_engine, ["accepted done"]);
+    assert_eq!(
+        to_gui(&out),
+        [
+            "id name Old Engine 2.1",
+            "option name Hash type spin default 16 min 1 max 65536",
+            "option name Resign Threshold type spin default 800 min 100 max 5000",
+            "option name Book type check default true",
+            "uciok",
+        ]
+    );
+    adapter
+}
+
+#[test]
+fn cecp_parsing() {
+    let thinking = CecpThinking::parse_from("9. -35 152 120345 Nf3 d5 g3").unwrap();
+    assert_eq!(thinking.depth, 9);
+    assert_eq!(thinking.score, -35);
+    assert_eq!(thinking.time, Duration::from_millis(1520));
+    assert_eq!(thinking.pv, "Nf3 d5 g3");
+    let thinking = CecpThinking::parse_from("4 10 3 50 4 1000 0\te2e4").unwrap();
+    assert_eq!(thinking.pv, "e2e4");
+    assert!(CecpThinking::parse_from("move e2e4").is_none());
+
+    assert_eq!(cecp_score_value(100003), UciScoreValue::Mate(3));
+    assert_eq!(cecp_score_value(-100002), UciScoreValue::Mate(-2));
+    assert_eq!(cecp_score_value(42), UciScoreValue::Cp(42));
+
+    assert_eq!(
+        parse_cecp_features("a=1 b=\"x y\" c=0"),
+        [
+            ("a".to_owned(), "1".to_owned()),
+            ("b".to_owned(), "x y".to_owned()),
+            ("c".to_owned(), "0".to_owned()),
+        ]
+    );
+    assert_eq!(
+        parse_cecp_feature_option("Style -combo Solid /// * Normal "),
+        Some((
+            "Style".to_owned(),
+            UciOptionInfo::Combo {
+                default: "Normal".to_owned(),
+                labels: vec!["Solid".to_owned(), "Normal".to_owned()],
+            }
+        ))
+    );
+    assert_eq!(
+        parse_cecp_feature_option("Book File -file book.bin"),
+        Some((
+            "Book File".to_owned(),
+            UciOptionInfo::String {
+                default: "book.bin".to_owned()
+            }
+        ))
+    );
+    assert_eq!(parse_cecp_feature_option("Nothing -frobnicate"), None);
+
+    let board = Board::default();
+    assert_eq!(parse_cecp_move(&board, "Nf3", false), "g1f3".parse().ok());
+    assert_eq!(parse_cecp_move(&board, "e2e4", false), "e2e4".parse().ok());
+    assert_eq!(parse_cecp_move(&board, "e2e5", false), None);
+}
+
+#[test]
+fn xboard2uci_game() {
+    let mut adapter = handshake();
+    let out = send(&mut adapter, "isready");
+    assert_eq!(out.to_engine, ["ping 1"]);
+    assert_eq!(to_gui(&adapter.engine_line("pong 1")), ["readyok"]);
+
+    let out = send(&mut adapter, "setoption name Hash value 128");
+    assert_eq!(out.to_engine, ["memory 128"]);
+    let out = send(&mut adapter, "setoption name Book value false");
+    assert_eq!(out.to_engine, ["option Book=0"]);
+
+    send(&mut adapter, "ucinewgame");
+    send(&mut adapter, "position startpos moves e2e4");
+    let out = send(
+        &mut adapter,
+        "go wtime 60000 btime 59000 winc 1000 binc 1000",
+    );
+    assert_eq!(
+        out.to_engine,
+        [
+            "new",
+            "force",
+            "post",
+            "usermove e2e4",
+            "level 0 0:59 1",
+            "time 5900",
+            "otim 6000",
+            "go",
+        ]
+    );
+    let out = adapter.engine_line("12 -15 250 400000 e5 Nf3 Nc6");
+    assert_eq!(
+        to_gui(&out),
+        ["info depth 12 time 2500 nodes 400000 pv e7e5 g1f3 b8c6 score cp -15"]
+    );
+    let out = adapter.engine_line("move e7e5");
+    assert_eq!(to_gui(&out), ["bestmove e7e5 ponder g1f3"]);
+
+    // Only the new moves are sent while the game continues.
+    send(
+        &mut adapter,
+        "position startpos moves e2e4 e7e5 g1f3 b8c6 f1b5",
+    );
+    let out = send(
+        &mut adapter,
+        "go wtime 58000 btime 55000 winc 1000 binc 1000",
+    );
+    assert_eq!(
+        out.to_engine,
+        [
+            "force",
+            "usermove g1f3",
+            "usermove b8c6",
+            "usermove f1b5",
+            "time 5500",
+            "otim 5800",
+            "go",
+        ]
+    );
+    let out = send(&mut adapter, "stop");
+    assert_eq!(out.to_engine, ["?"]);
+    let out = adapter.engine_line("move a7a6");
+    assert_eq!(to_gui(&out), ["bestmove a7a6"]);
+
+    let out = adapter.engine_line("Illegal move: foo");
+    assert_eq!(to_gui(&out), ["info string Illegal move: foo"]);
+}
+
+#[test]
+fn xboard2uci_analysis() {
+    let mut adapter = handshake();
+    send(
+        &mut adapter,
+        "position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+    );
+    let out = send(&mut adapter, "go infinite");
+    assert_eq!(
+        out.to_engine,
+        [
+            "new",
+            "force",
+            "setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "post",
+            "analyze",
+        ]
+    );
+    let out = adapter.engine_line("3 100001 5 200 Ra8#");
+    assert_eq!(
+        to_gui(&out),
+        ["info depth 3 time 50 nodes 200 pv a1a8 score mate 1"]
+    );
+    let out = send(&mut adapter, "stop");
+    assert_eq!(out.to_engine, ["exit"]);
+    assert_eq!(to_gui(&out), ["bestmove a1a8"]);
+    // Thinking after the search has stopped is not passed on.
+    assert!(adapter.engine_line("4 100001 6 300 Ra8#").to_gui.is_empty());
+
+    // A depth limit set by `sd` is cleared by starting a new game.
+    let out = send(&mut adapter, "go depth 5");
+    assert_eq!(out.to_engine, ["force", "sd 5", "go"]);
+    adapter.engine_line("move a1a8");
+    let out = send(&mut adapter, "go movetime 1500");
+    assert_eq!(
+        out.to_engine,
+        [
+            "new",
+            "force",
+            "setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "post",
+            "st 2",
+            "go",
+        ]
+    );
+}
+
+#[test]
+fn xboard2uci_pipes() {
+    let (gui_reader, mut gui_input) = pipe().unwrap();
+    let (gui_output, gui_writer) = pipe().unwrap();
+    let (engine_input, engine_writer) = pipe().unwrap();
+    let (engine_reader, mut engine_output) = pipe().unwrap();
+
+    let engine = thread::spawn(move || {
+        let mut commands = Vec::new();
+        for line in BufReader::new(engine_input).lines() {
+            let line = line.unwrap();
+            let reply = match line.as_str() {
+                "protover 2" => {
+                    "feature myname=\"Pipe \" ping=1\n\
+                     feature option=\"Style -combo Solid var Sharp /// *Normal\"\n\
+                     feature done=1\n"
+                }
+                "go" => "move e7e5\n",
+                _ if line.starts_with("ping") => &format!("pong {}\n", &line[5..]),
+                _ => "",
+            };
+            engine_output.write_all(reply.as_bytes()).unwrap();
+            commands.push(line);
+        }
+        commands
+    });
+    let adapter = thread::spawn(move || {
+        run_xboard2uci(
+            Xboard2Uci::new(),
+            BufReader::new(gui_reader),
+            gui_writer,
+            BufReader::new(engine_reader),
+            engine_writer,
+        )
+    });
+
+    let mut gui_output = BufReader::new(gui_output).lines().map(Result::unwrap);
+    writeln!(gui_input, "uci").unwrap();
+    assert_eq!(gui_output.next().unwrap(), "id name Pipe");
+    // The combo label cannot be written in UCI, so the option is reported instead.
+    assert_eq!(
+        gui_output.next().unwrap(),
+        "info string cannot format var: string contains reserved token `var`"
+    );
+    assert_eq!(gui_output.next().unwrap(), "uciok");
+    writeln!(gui_input, "isready").unwrap();
+    assert_eq!(gui_output.next().unwrap(), "readyok");
+    writeln!(gui_input, "position startpos moves e2e4\ngo movetime 100").unwrap();
+    assert_eq!(gui_output.next().unwrap(), "bestmove e7e5");
+    writeln!(gui_input, "quit").unwrap();
+    adapter.join().unwrap().unwrap();
+
+    let commands = engine.join().unwrap();
+    assert_eq!(commands.last().map(String::as_str), Some("quit"));
+    assert!(commands.iter().any(|line| line == "e2e4"));
+}
+
+#[test]
+fn xboard2uci_feature_timeout() {
+    let mut adapter = Xboard2Uci::new();
+    adapter.start();
+    send(&mut adapter, "uci");
+    assert!(adapter.awaiting_features());
+    let out = adapter.finish_features();
+    assert_eq!(to_gui(&out), ["id name xboard engine", "uciok"]);
+    assert!(!adapter.awaiting_features());
+    assert!(matches!(
+        send(&mut adapter, "isready").to_gui[..],
+        [UciRemark::ReadyOk]
+    ));
+}
+
+#[test]
+fn xboard2uci_feature_done_0() {
+    let mut adapter = Xboard2Uci::new();
+    adapter.start();
+    send(&mut adapter, "uci");
+    assert!(adapter.features_time_out());
+    let out = adapter.engine_line("feature done=0");
+    assert_eq!(out.to_engine, ["accepted done"]);
+    assert!(out.to_gui.is_empty());
+    // The engine asked for more time, so only `feature done=1` finishes.
+    assert!(adapter.awaiting_features());
+    assert!(!adapter.features_time_out());
+    let out = adapter.engine_line("feature myname=\"Slow\" done=1");
+    assert_eq!(to_gui(&out), ["id name Slow", "uciok"]);
+    assert!(!adapter.awaiting_features());
+}
+
+#[test]
+fn xboard2uci_nodes_and_mate() {
+    let mut adapter = handshake();
+    send(&mut adapter, "position startpos moves e2e4");
+    // Without a CECP equivalent, the engine plays at a default level
+    // rather than analyzing forever.
+    let out = send(&mut adapter, "go nodes 1000");
+    assert_eq!(
+        out.to_engine,
+        [
+            "new",
+            "force",
+            "post",
+            "usermove e2e4",
+            "level 40 5 0",
+            "go"
+        ]
+    );
+    let out = adapter.engine_line("move e7e5");
+    assert_eq!(to_gui(&out), ["bestmove e7e5"]);
+
+    send(&mut adapter, "position startpos moves e2e4 e7e5");
+    let out = send(&mut adapter, "go mate 3");
+    assert_eq!(out.to_engine, ["force", "level 40 5 0", "go"]);
+    let out = adapter.engine_line("move g1f3");
+    assert_eq!(to_gui(&out), ["bestmove g1f3"]);
+}